@@ -73,7 +73,7 @@ function renderBadge(shadow: ShadowRoot, host: HTMLElement, result: AnalyzeRespo
   style.textContent = BADGE_CSS;
   shadow.appendChild(style);
 
-  const heuristicsOnly = result.breakdown.llm_score === null;
+  const heuristicsOnly = result.label === "uncertain";
   const variant = getVariant(result.score);
   const label = getLabel(result.score, heuristicsOnly);
 
@@ -30,7 +30,7 @@ function getPostUrl(item: HistoryItem): string | null {
 
 export function ScoreCard({ item }: Props) {
   const [expanded, setExpanded] = useState(false);
-  const heuristicsOnly = item.llm_score === null;
+  const heuristicsOnly = item.label === "uncertain";
   const style = getScoreStyle(item.score, heuristicsOnly);
   const signals: string[] = (() => {
     try { return JSON.parse(item.signals); } catch { return []; }
@@ -7,14 +7,146 @@ export interface AnalyzeRequest {
   author?: string;
 }
 
+export interface Evidence {
+  signal: string;
+  text: string;
+  start: number;
+  end: number;
+  char_start: number;
+  char_end: number;
+}
+
+export interface Removed {
+  kind: "url" | "mention" | "cashtag" | "hashtag" | "quote";
+  text: string;
+  start: number;
+  end: number;
+  char_start: number;
+  char_end: number;
+}
+
+export interface Contribution {
+  measurement: string;
+  signal: string | null;
+  value: number | null;
+  score: number;
+  weight: number;
+  share: number;
+}
+
+export interface DetectorResult {
+  name: string;
+  score: number;
+  confidence: number;
+  weight: number;
+  error: string | null;
+}
+
+export interface WatermarkResult {
+  scheme: string;
+  detected: boolean;
+  z_score: number;
+  tokens: number;
+  green: number;
+}
+
+export interface Neighbour {
+  id: string;
+  corpus: string;
+  label: "ai" | "human";
+  similarity: number;
+  content_preview: string;
+}
+
+export interface NearDuplicate {
+  id: string;
+  author: string | null;
+  platform: string;
+  score: number;
+  similarity: number;
+  content_preview: string;
+  created_at: string;
+}
+
+export interface NearDuplicates {
+  count: number;
+  authors: string[];
+  closest: NearDuplicate[];
+}
+
+export interface TemplateCluster {
+  size: number;
+  authors: string[];
+  first_seen: string;
+  last_seen: string;
+  posts: NearDuplicate[];
+}
+
+export interface TemplatesResponse {
+  clusters: TemplateCluster[];
+}
+
+export interface CampaignAuthor {
+  author: string;
+  posts: number;
+}
+
+export interface SharedTemplate {
+  size: number;
+  authors: string[];
+  content_preview: string;
+}
+
+export interface Campaign {
+  id: string;
+  authors: CampaignAuthor[];
+  platforms: string[];
+  post_count: number;
+  average_score: number;
+  first_seen: string;
+  last_seen: string;
+  templates: SharedTemplate[];
+  template_links: number;
+  structural_links: number;
+  updated_at: string;
+}
+
+export interface CampaignPost {
+  id: string;
+  author: string;
+  platform: string;
+  score: number;
+  signals: string[];
+  content_preview: string;
+  created_at: string;
+}
+
+export interface CampaignDetail extends Campaign {
+  posts: CampaignPost[];
+}
+
+export interface CampaignsResponse {
+  campaigns: Campaign[];
+  total: number;
+}
+
 export interface AnalyzeResponse {
   score: number;
   confidence: number;
   label: "human" | "mixed" | "likely_ai" | "ai" | "uncertain";
+  language: string | null;
+  removed: Removed[];
   breakdown: {
     llm_score: number | null;
     heuristic_score: number;
+    transformer_score: number | null;
     signals: string[];
+    evidence: Evidence[];
+    contributions: Contribution[];
+    detectors: DetectorResult[];
+    watermark: WatermarkResult | null;
+    near_duplicates: NearDuplicates | null;
+    neighbours: Neighbour[] | null;
   };
 }
 
@@ -31,6 +163,12 @@ export interface HistoryItem {
   llm_score: number | null;
   heuristic_score: number;
   signals: string;
+  evidence: string;
+  contributions: string;
+  language: string | null;
+  removed: string;
+  detectors: string;
+  watermark: string | null;
   created_at: string;
 }
 
@@ -0,0 +1,53 @@
+//! Throughput of the heuristic engine on social-sized posts up to the 50,000-char
+//! limit accepted by `/api/analyze`, plus the line-heavy and many-sentence
+//! shapes that break per-line or per-sentence rescans. Run with
+//! `cargo bench --bench heuristics`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+use aidetector_server::services::heuristics;
+use aidetector_server::services::rules::Rules;
+
+const POST: &str = "Most people think success is about working longer hours. It's not.\n\n\
+    I spent 3 years grinding 16-hour days — and got nowhere, tbh.\n\n\
+    Here's what actually moved the needle:\n\
+    • Deep work blocks before 9 a.m.\n\
+    • Saying no to 80% of meetings\n\
+    • Shipping every week, e.g. small features at $1.5k each\n\n\
+    lol my cat still thinks I work for her... gonna be honest, she's right!!\n\
+    Let's dive in. The key takeaway? Consistency beats intensity every single time.\n\n";
+
+fn payload(chars: usize) -> String {
+    POST.chars().cycle().take(chars).collect()
+}
+
+fn analyze(c: &mut Criterion) {
+    let rules = Rules::builtin();
+    let mut group = c.benchmark_group("heuristics::analyze");
+
+    for size in [500, 5_000, 50_000] {
+        let text = payload(size);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &text, |b, text| {
+            b.iter(|| heuristics::analyze(black_box(text), &rules, None))
+        });
+    }
+
+    // Blank lines and one-word sentences at the size limit
+    let shapes = [
+        ("blank_lines", format!("Hello{}world", "\n".repeat(49_990))),
+        ("short_sentences", " a.".repeat(16_600)),
+    ];
+    for (shape, text) in shapes {
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::new(shape, text.len()), &text, |b, text| {
+            b.iter(|| heuristics::analyze(black_box(text), &rules, None))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, analyze);
+criterion_main!(benches);
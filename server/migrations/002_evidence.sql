@@ -0,0 +1,2 @@
+-- Span-level evidence for heuristic signals (JSON array of {signal, text, start, end, char_start, char_end})
+ALTER TABLE analyses ADD COLUMN evidence TEXT;
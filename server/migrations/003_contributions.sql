@@ -0,0 +1,2 @@
+-- Per-signal votes behind the heuristic score (JSON array of {measurement, signal, value, score, weight, share})
+ALTER TABLE analyses ADD COLUMN contributions TEXT;
@@ -0,0 +1,2 @@
+-- Detected language of the analyzed content (ISO 639-1), NULL when undetermined
+ALTER TABLE analyses ADD COLUMN language TEXT;
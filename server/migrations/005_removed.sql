@@ -0,0 +1,2 @@
+-- Content cut before scoring (JSON array of {kind, text, start, end, char_start, char_end})
+ALTER TABLE analyses ADD COLUMN removed TEXT;
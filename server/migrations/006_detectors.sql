@@ -0,0 +1,2 @@
+-- Per-detector results of the ensemble (JSON array of {name, score, confidence, weight})
+ALTER TABLE analyses ADD COLUMN detectors TEXT;
@@ -0,0 +1,2 @@
+-- Watermark test of the best-matching known scheme (JSON {scheme, detected, z_score, tokens, green}), NULL when none are configured
+ALTER TABLE analyses ADD COLUMN watermark TEXT;
@@ -0,0 +1,4 @@
+-- Style features of each post (JSON {features, floors}) for per-author baselines
+ALTER TABLE analyses ADD COLUMN style TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_author_platform ON analyses(author, platform, created_at);
@@ -0,0 +1,12 @@
+-- MinHash signature of each post's word shingles (64 little-endian u32), NULL for posts too short to fingerprint
+ALTER TABLE analyses ADD COLUMN minhash BLOB;
+
+-- Locality-sensitive band keys of each signature (16 per post); posts sharing one are near-duplicate candidates
+CREATE TABLE IF NOT EXISTS minhash_bands (
+    analysis_id TEXT NOT NULL REFERENCES analyses(id),
+    band INTEGER NOT NULL,
+    value INTEGER NOT NULL,
+    PRIMARY KEY (analysis_id, band)
+);
+
+CREATE INDEX IF NOT EXISTS idx_minhash_bands ON minhash_bands(band, value);
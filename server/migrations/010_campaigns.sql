@@ -0,0 +1,11 @@
+-- Campaigns found by the latest clustering run, replaced as a whole each run
+CREATE TABLE IF NOT EXISTS campaigns (
+    id TEXT PRIMARY KEY,
+    authors INTEGER NOT NULL,
+    post_count INTEGER NOT NULL,
+    last_seen TEXT NOT NULL,
+    campaign TEXT NOT NULL, -- JSON summary
+    posts TEXT NOT NULL     -- JSON array of linked posts
+);
+
+CREATE INDEX IF NOT EXISTS idx_campaigns_size ON campaigns(authors DESC, last_seen DESC);
@@ -0,0 +1,18 @@
+-- Nearest labeled reference examples of each post (JSON array), NULL without an embeddings provider
+ALTER TABLE analyses ADD COLUMN neighbours TEXT;
+
+-- Labeled reference corpora (`aidetector-server import`) and their embeddings
+-- (little-endian f32), one vector per example and embedding model
+CREATE TABLE IF NOT EXISTS reference_examples (
+    id TEXT PRIMARY KEY,
+    corpus TEXT NOT NULL,
+    label TEXT NOT NULL CHECK (label IN ('ai', 'human')),
+    content TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    model TEXT NOT NULL,
+    embedding BLOB NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (corpus, content_hash, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_reference_examples_model ON reference_examples(model);
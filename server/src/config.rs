@@ -8,9 +8,19 @@ use std::path::PathBuf;
 pub enum LlmProvider {
     OpenRouter,
     Anthropic,
+    /// Local transformer classifier (`transformer` feature), no network.
+    Transformer,
     None,
 }
 
+/// An OpenAI-compatible server and the model to use on it.
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    /// Base URL up to and including `/v1`.
+    pub url: String,
+    pub model: String,
+}
+
 #[derive(Clone)]
 pub struct Config {
     pub port: u16,
@@ -23,6 +33,26 @@ pub struct Config {
     // Anthropic
     pub anthropic_api_key: String,
     pub anthropic_model: String,
+    // Heuristic rules
+    pub rules_path: Option<PathBuf>,
+    pub rules_reload_secs: u64,
+    // Campaign clustering job interval, 0 to disable
+    pub campaigns_interval_secs: u64,
+    // Trained classifier model (`aidetector-server train`)
+    pub classifier_path: Option<PathBuf>,
+    // N-gram language model (ARPA) for the perplexity detector
+    pub perplexity_model_path: Option<PathBuf>,
+    // Local transformer classifier directory (`transformer` feature)
+    pub transformer_model_path: Option<PathBuf>,
+    // Local OpenAI-compatible servers for the logprobs detector; the performer
+    // model enables Binoculars scoring
+    pub logprobs: Option<Endpoint>,
+    pub logprobs_performer: Option<Endpoint>,
+    // Known watermark schemes (`watermark` feature)
+    pub watermark_path: Option<PathBuf>,
+    // OpenAI-compatible embeddings server for the reference corpus detector
+    pub embeddings: Option<Endpoint>,
+    pub embeddings_api_key: String,
 }
 
 impl Config {
@@ -52,6 +82,12 @@ impl Config {
             .or_else(|| env::var("ANTHROPIC_API_MODEL").ok().filter(|s| !s.is_empty()))
             .unwrap_or_else(|| "claude-sonnet-4-5-20250929".to_string());
 
+        // Local transformer: config.json, tokenizer.json and model.safetensors
+        let transformer_model_path = env::var("TRANSFORMER_MODEL_PATH")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+
         // Provider selection: explicit flag > auto-detect
         let llm_provider = match env::var("PRIMARY_AI_PROVIDER")
             .unwrap_or_default()
@@ -70,12 +106,20 @@ impl Config {
                 }
                 LlmProvider::OpenRouter
             }
+            "transformer" | "local" => {
+                if transformer_model_path.is_none() {
+                    panic!("PRIMARY_AI_PROVIDER=transformer but TRANSFORMER_MODEL_PATH is empty");
+                }
+                LlmProvider::Transformer
+            }
             _ => {
                 // Auto-detect: prefer anthropic if configured, else openrouter, else heuristics-only
                 if !anthropic_api_key.is_empty() {
                     LlmProvider::Anthropic
                 } else if !openrouter_api_key.is_empty() {
                     LlmProvider::OpenRouter
+                } else if transformer_model_path.is_some() {
+                    LlmProvider::Transformer
                 } else {
                     tracing::warn!("No LLM provider configured — running in heuristics-only mode. Set ANTHROPIC_API_KEY, ANTHROPIC_MAX_SETUP_TOKEN, or OPENROUTER_API_KEY to enable LLM analysis.");
                     LlmProvider::None
@@ -85,6 +129,65 @@ impl Config {
 
         tracing::info!("LLM provider: {:?}", llm_provider);
 
+        // Heuristic rules file (built-in rules when unset)
+        let rules_path = env::var("RULES_PATH")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+        let rules_reload_secs = env::var("RULES_RELOAD_SECS")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("RULES_RELOAD_SECS must be a number");
+
+        // Campaign clustering job
+        let campaigns_interval_secs = env::var("CAMPAIGNS_INTERVAL_SECS")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .expect("CAMPAIGNS_INTERVAL_SECS must be a number");
+
+        // Classifier detector (off when unset)
+        let classifier_path = env::var("CLASSIFIER_PATH")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+
+        // Perplexity detector (off when unset)
+        let perplexity_model_path = env::var("PERPLEXITY_MODEL_PATH")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+
+        // Watermark detector (off when unset)
+        let watermark_path = env::var("WATERMARK_PATH")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+
+        // Logprobs detector (off when unset)
+        let logprobs_url = env::var("LOGPROBS_URL").ok().filter(|s| !s.is_empty());
+        let logprobs = logprobs_url.clone().map(|url| Endpoint {
+            url,
+            model: env::var("LOGPROBS_MODEL").unwrap_or_default(),
+        });
+        let logprobs_performer = env::var("LOGPROBS_PERFORMER_MODEL")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|model| Endpoint {
+                url: env::var("LOGPROBS_PERFORMER_URL")
+                    .ok()
+                    .filter(|s| !s.is_empty())
+                    .or_else(|| logprobs_url.clone())
+                    .expect("LOGPROBS_PERFORMER_MODEL needs LOGPROBS_URL or LOGPROBS_PERFORMER_URL"),
+                model,
+            });
+        if logprobs_performer.is_some() && logprobs.is_none() {
+            panic!("LOGPROBS_PERFORMER_MODEL needs LOGPROBS_URL for the observer model");
+        }
+
+        // Embeddings detector (off when unset)
+        let embeddings = embeddings_endpoint();
+        let embeddings_api_key = env::var("EMBEDDINGS_API_KEY").unwrap_or_default();
+
         Self {
             port,
             database_url,
@@ -94,10 +197,31 @@ impl Config {
             openrouter_model,
             anthropic_api_key,
             anthropic_model,
+            rules_path,
+            rules_reload_secs,
+            campaigns_interval_secs,
+            classifier_path,
+            perplexity_model_path,
+            transformer_model_path,
+            logprobs,
+            logprobs_performer,
+            watermark_path,
+            embeddings,
+            embeddings_api_key,
         }
     }
 }
 
+/// `EMBEDDINGS_URL` and `EMBEDDINGS_MODEL`, shared with the `import` subcommand.
+pub fn embeddings_endpoint() -> Option<Endpoint> {
+    let url = env::var("EMBEDDINGS_URL").ok().filter(|s| !s.is_empty())?;
+    let model = env::var("EMBEDDINGS_MODEL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .expect("EMBEDDINGS_URL needs EMBEDDINGS_MODEL");
+    Some(Endpoint { url, model })
+}
+
 // --- auth-profiles.json reader ---
 
 /// Attempt to read an Anthropic token from ~/.claude/auth-profiles.json
@@ -2,7 +2,8 @@ use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Row, SqlitePool};
 use std::str::FromStr;
 
-use crate::models::{AnalysisRecord, HistoryItem};
+use crate::models::{AnalysisRecord, Campaign, CampaignDetail, Fingerprint, FlaggedPost, HistoryItem, ReferenceRecord};
+use crate::services::templates;
 
 pub async fn init_pool(database_url: &str) -> SqlitePool {
     let options = SqliteConnectOptions::from_str(database_url)
@@ -16,23 +17,36 @@ pub async fn init_pool(database_url: &str) -> SqlitePool {
         .expect("Failed to connect to database");
 
     // Run migrations
-    sqlx::query(include_str!("../migrations/001_init.sql"))
-        .execute(&pool)
+    sqlx::migrate!("./migrations")
+        .run(&pool)
         .await
         .expect("Failed to run migrations");
 
     pool
 }
 
-pub async fn find_by_hash(pool: &SqlitePool, content_hash: &str) -> Option<AnalysisRecord> {
+/// Latest analysis of the same content on the same platform (platforms score
+/// differently) by the same author (a copy by another account is a new post for
+/// near-duplicate and campaign detection).
+pub async fn find_by_hash(
+    pool: &SqlitePool,
+    content_hash: &str,
+    platform: &str,
+    author: Option<&str>,
+) -> Option<AnalysisRecord> {
     sqlx::query_as::<_, AnalysisRecord>(
         "SELECT id, content_hash, platform, post_id, author,
                 score, confidence, label, llm_score, heuristic_score,
-                signals, created_at
-         FROM analyses WHERE content_hash = ?
+                signals, COALESCE(evidence, '[]') as evidence,
+                COALESCE(contributions, '[]') as contributions, language,
+                COALESCE(removed, '[]') as removed,
+                COALESCE(detectors, '[]') as detectors, watermark, style, minhash, neighbours, created_at
+         FROM analyses WHERE content_hash = ? AND platform = ? AND author IS ?
          ORDER BY created_at DESC LIMIT 1"
     )
     .bind(content_hash)
+    .bind(platform)
+    .bind(author)
     .fetch_optional(pool)
     .await
     .ok()
@@ -44,9 +58,10 @@ pub async fn insert_analysis_full(
     record: &AnalysisRecord,
     content: &str,
 ) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
     sqlx::query(
-        "INSERT INTO analyses (id, content_hash, content, platform, post_id, author, score, confidence, label, llm_score, heuristic_score, signals, created_at)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        "INSERT INTO analyses (id, content_hash, content, platform, post_id, author, score, confidence, label, llm_score, heuristic_score, signals, evidence, contributions, language, removed, detectors, watermark, style, minhash, neighbours, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&record.id)
     .bind(&record.content_hash)
@@ -60,10 +75,140 @@ pub async fn insert_analysis_full(
     .bind(record.llm_score)
     .bind(record.heuristic_score)
     .bind(&record.signals)
+    .bind(&record.evidence)
+    .bind(&record.contributions)
+    .bind(&record.language)
+    .bind(&record.removed)
+    .bind(&record.detectors)
+    .bind(&record.watermark)
+    .bind(&record.style)
+    .bind(&record.minhash)
+    .bind(&record.neighbours)
     .bind(&record.created_at)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
-    Ok(())
+
+    if let Some(minhash) = &record.minhash {
+        for (band, value) in templates::bands(&templates::from_bytes(minhash)).into_iter().enumerate() {
+            sqlx::query("INSERT INTO minhash_bands (analysis_id, band, value) VALUES (?, ?, ?)")
+                .bind(&record.id)
+                .bind(band as i64)
+                .bind(value)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await
+}
+
+/// Latest `limit` analyses sharing a MinHash band with `signature`, any platform.
+pub async fn find_fingerprints(pool: &SqlitePool, signature: &[u32], limit: i64) -> Result<Vec<Fingerprint>, sqlx::Error> {
+    let bands = templates::bands(signature);
+    let sql = format!(
+        "SELECT id, author, platform, score, minhash, SUBSTR(content, 1, 150) as content_preview, created_at
+         FROM analyses
+         WHERE id IN (SELECT analysis_id FROM minhash_bands WHERE {})
+         ORDER BY created_at DESC
+         LIMIT ?",
+        vec!["(band = ? AND value = ?)"; bands.len()].join(" OR ")
+    );
+    let mut query = sqlx::query_as::<_, Fingerprint>(&sql);
+    for (band, value) in bands.into_iter().enumerate() {
+        query = query.bind(band as i64).bind(value);
+    }
+    query.bind(limit).fetch_all(pool).await
+}
+
+/// Latest `limit` analyses since `since` sharing a MinHash band with another
+/// analysis since then, for template clustering.
+pub async fn get_colliding_fingerprints(pool: &SqlitePool, since: &str, limit: i64) -> Result<Vec<Fingerprint>, sqlx::Error> {
+    sqlx::query_as::<_, Fingerprint>(
+        "SELECT id, author, platform, score, minhash, SUBSTR(content, 1, 150) as content_preview, created_at
+         FROM analyses
+         WHERE created_at >= ?
+           AND id IN (SELECT x.analysis_id FROM minhash_bands x
+                      JOIN minhash_bands y ON x.band = y.band AND x.value = y.value
+                                          AND x.analysis_id != y.analysis_id
+                      WHERE x.analysis_id IN (SELECT id FROM analyses WHERE created_at >= ?)
+                        AND y.analysis_id IN (SELECT id FROM analyses WHERE created_at >= ?))
+         ORDER BY created_at DESC
+         LIMIT ?"
+    )
+    .bind(since)
+    .bind(since)
+    .bind(since)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Analyses by named authors scoring at least `min_score` since `since`, oldest first.
+pub async fn get_flagged_posts(pool: &SqlitePool, min_score: i32, since: &str) -> Result<Vec<FlaggedPost>, sqlx::Error> {
+    sqlx::query_as::<_, FlaggedPost>(
+        "SELECT id, author, platform, score, COALESCE(signals, '[]') as signals, minhash,
+                SUBSTR(content, 1, 150) as content_preview, created_at
+         FROM analyses
+         WHERE score >= ? AND created_at >= ?
+           AND author IS NOT NULL AND author != '' AND author != 'unknown'
+         ORDER BY created_at"
+    )
+    .bind(min_score)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace every stored campaign with `campaigns`.
+pub async fn replace_campaigns(pool: &SqlitePool, campaigns: &[CampaignDetail]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM campaigns").execute(&mut *tx).await?;
+    for detail in campaigns {
+        let campaign = &detail.campaign;
+        sqlx::query(
+            "INSERT INTO campaigns (id, authors, post_count, last_seen, campaign, posts)
+             VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&campaign.id)
+        .bind(campaign.authors.len() as i64)
+        .bind(campaign.post_count as i64)
+        .bind(&campaign.last_seen)
+        .bind(serde_json::to_string(campaign).unwrap_or_default())
+        .bind(serde_json::to_string(&detail.posts).unwrap_or_else(|_| "[]".to_string()))
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
+
+/// Stored campaigns, most authors first, and how many there are.
+pub async fn get_campaigns(pool: &SqlitePool, limit: i64, offset: i64) -> Result<(Vec<Campaign>, i64), sqlx::Error> {
+    let rows = sqlx::query_scalar::<_, String>(
+        "SELECT campaign FROM campaigns
+         ORDER BY authors DESC, last_seen DESC
+         LIMIT ? OFFSET ?"
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+    let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM campaigns")
+        .fetch_one(pool)
+        .await?;
+    let campaigns = rows.iter().filter_map(|json| serde_json::from_str(json).ok()).collect();
+    Ok((campaigns, total))
+}
+
+pub async fn get_campaign(pool: &SqlitePool, id: &str) -> Result<Option<CampaignDetail>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (String, String)>("SELECT campaign, posts FROM campaigns WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.and_then(|(campaign, posts)| {
+        Some(CampaignDetail {
+            campaign: serde_json::from_str(&campaign).ok()?,
+            posts: serde_json::from_str(&posts).ok()?,
+        })
+    }))
 }
 
 pub async fn get_history(pool: &SqlitePool, limit: i64, offset: i64, author: Option<&str>) -> Result<(Vec<HistoryItem>, i64), sqlx::Error> {
@@ -71,7 +216,11 @@ pub async fn get_history(pool: &SqlitePool, limit: i64, offset: i64, author: Opt
         Some(a) => {
             let items = sqlx::query_as::<_, HistoryItem>(
                 "SELECT id, content, SUBSTR(content, 1, 150) as content_preview, platform, post_id, author,
-                        score, confidence, label, llm_score, heuristic_score, signals, created_at
+                        score, confidence, label, llm_score, heuristic_score, signals,
+                        COALESCE(evidence, '[]') as evidence,
+                        COALESCE(contributions, '[]') as contributions, language,
+                        COALESCE(removed, '[]') as removed,
+                        COALESCE(detectors, '[]') as detectors, watermark, created_at
                  FROM analyses WHERE author = ?
                  ORDER BY created_at DESC
                  LIMIT ? OFFSET ?"
@@ -92,7 +241,11 @@ pub async fn get_history(pool: &SqlitePool, limit: i64, offset: i64, author: Opt
         None => {
             let items = sqlx::query_as::<_, HistoryItem>(
                 "SELECT id, content, SUBSTR(content, 1, 150) as content_preview, platform, post_id, author,
-                        score, confidence, label, llm_score, heuristic_score, signals, created_at
+                        score, confidence, label, llm_score, heuristic_score, signals,
+                        COALESCE(evidence, '[]') as evidence,
+                        COALESCE(contributions, '[]') as contributions, language,
+                        COALESCE(removed, '[]') as removed,
+                        COALESCE(detectors, '[]') as detectors, watermark, created_at
                  FROM analyses
                  ORDER BY created_at DESC
                  LIMIT ? OFFSET ?"
@@ -113,6 +266,37 @@ pub async fn get_history(pool: &SqlitePool, limit: i64, offset: i64, author: Opt
     Ok((items, total))
 }
 
+/// Style JSON of the author's latest `limit` analyses on `platform`, newest first.
+pub async fn get_author_styles(
+    pool: &SqlitePool,
+    author: &str,
+    platform: &str,
+    limit: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT style FROM analyses
+         WHERE author = ? AND platform = ? AND style IS NOT NULL
+         ORDER BY created_at DESC
+         LIMIT ?"
+    )
+    .bind(author)
+    .bind(platform)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// (content, platform, llm_score) of every stored analysis an LLM scored, for training.
+pub async fn get_llm_scored(pool: &SqlitePool) -> Result<Vec<(String, String, i32)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, String, i32)>(
+        "SELECT content, platform, llm_score FROM analyses
+         WHERE llm_score IS NOT NULL AND content IS NOT NULL
+         ORDER BY created_at"
+    )
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn get_authors(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error> {
     let rows = sqlx::query_scalar::<_, String>(
         "SELECT DISTINCT author FROM analyses WHERE author IS NOT NULL AND author != 'unknown' ORDER BY author"
@@ -121,3 +305,69 @@ pub async fn get_authors(pool: &SqlitePool) -> Result<Vec<String>, sqlx::Error>
     .await?;
     Ok(rows)
 }
+
+/// Count and newest rowid of the reference examples embedded with `model`; any
+/// import or deletion changes it.
+pub async fn references_version(pool: &SqlitePool, model: &str) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as("SELECT COUNT(*), COALESCE(MAX(rowid), 0) FROM reference_examples WHERE model = ?")
+        .bind(model)
+        .fetch_one(pool)
+        .await
+}
+
+/// Every reference example embedded with `model`, for the in-memory index.
+pub async fn get_references(pool: &SqlitePool, model: &str) -> Result<Vec<ReferenceRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ReferenceRecord>(
+        "SELECT id, corpus, label, SUBSTR(content, 1, 150) as content_preview, embedding
+         FROM reference_examples WHERE model = ?"
+    )
+    .bind(model)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn reference_exists(pool: &SqlitePool, corpus: &str, content_hash: &str, model: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM reference_examples WHERE corpus = ? AND content_hash = ? AND model = ?"
+    )
+    .bind(corpus)
+    .bind(content_hash)
+    .bind(model)
+    .fetch_one(pool)
+    .await
+    .map(|n| n > 0)
+}
+
+pub async fn insert_reference(
+    pool: &SqlitePool,
+    corpus: &str,
+    label: &str,
+    content: &str,
+    content_hash: &str,
+    model: &str,
+    embedding: &[u8],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT OR IGNORE INTO reference_examples (id, corpus, label, content, content_hash, model, embedding)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(corpus)
+    .bind(label)
+    .bind(content)
+    .bind(content_hash)
+    .bind(model)
+    .bind(embedding)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Delete a reference corpus; returns how many examples it had.
+pub async fn delete_corpus(pool: &SqlitePool, corpus: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM reference_examples WHERE corpus = ?")
+        .bind(corpus)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
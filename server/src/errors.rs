@@ -6,6 +6,7 @@ use serde_json::json;
 #[derive(Debug)]
 pub enum AppError {
     BadRequest(String),
+    NotFound(String),
     Unauthorized,
     Internal(String),
     Database(sqlx::Error),
@@ -16,6 +17,7 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Invalid API key".to_string()),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             AppError::Database(e) => {
@@ -32,6 +34,18 @@ impl IntoResponse for AppError {
     }
 }
 
+/// The message a client would get, for reporting errors that don't fail the request.
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::BadRequest(msg) | AppError::NotFound(msg) | AppError::Internal(msg) => f.write_str(msg),
+            AppError::Unauthorized => f.write_str("Invalid API key"),
+            AppError::Database(_) => f.write_str("Database error"),
+            AppError::LlmApi(msg) => write!(f, "LLM API error: {msg}"),
+        }
+    }
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(e: sqlx::Error) -> Self {
         AppError::Database(e)
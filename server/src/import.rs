@@ -0,0 +1,152 @@
+//! `aidetector-server import`: load a labeled reference corpus for the embeddings detector.
+//!
+//! Reads a JSONL file of `{"content": "...", "label": "ai"}` lines (`label` is
+//! `ai` or `human`, optional with `--label`), embeds each post through
+//! `EMBEDDINGS_URL` / `EMBEDDINGS_MODEL` in batches, and stores it under
+//! `--corpus` in the `reference_examples` table. Posts the corpus already has
+//! for the same model are skipped, so re-running an import only embeds new
+//! lines; `--replace` clears the corpus first. A running server picks the
+//! changes up on its next analysis.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::config;
+use crate::db;
+use crate::errors::AppError;
+use crate::services::detector::hash_content;
+use crate::services::{embeddings, entities};
+
+const USAGE: &str = "Usage: aidetector-server import --jsonl <file> --corpus <name> [--label ai|human] \
+                     [--replace] [--db <url>]";
+
+/// Posts embedded per request.
+const BATCH: usize = 32;
+
+struct Options {
+    jsonl: PathBuf,
+    corpus: String,
+    label: Option<String>,
+    replace: bool,
+    db: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Line {
+    content: String,
+    label: Option<String>,
+}
+
+/// Run the subcommand with the arguments after `import`.
+pub async fn run(args: &[String]) -> Result<(), String> {
+    let options = parse(args)?;
+    let endpoint = config::embeddings_endpoint().ok_or("EMBEDDINGS_URL and EMBEDDINGS_MODEL must be set")?;
+    let api_key = std::env::var("EMBEDDINGS_API_KEY").unwrap_or_default();
+
+    let data = fs::read_to_string(&options.jsonl)
+        .map_err(|e| format!("Failed to read {}: {e}", options.jsonl.display()))?;
+    let examples: Vec<(String, String)> = data
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let line: Line = serde_json::from_str(line).map_err(|e| format!("Line {}: {e}", i + 1))?;
+            let label = line
+                .label
+                .or_else(|| options.label.clone())
+                .ok_or_else(|| format!("Line {}: no label and no --label", i + 1))?;
+            if label != "ai" && label != "human" {
+                return Err(format!("Line {}: label must be `ai` or `human`, not `{label}`", i + 1));
+            }
+            Ok((line.content, label))
+        })
+        .collect::<Result<_, String>>()?;
+
+    let url = options
+        .db
+        .clone()
+        .unwrap_or_else(|| std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:data.db".to_string()));
+    let pool = db::init_pool(&url).await;
+    let error = |e: sqlx::Error| format!("Database error: {e}");
+    if options.replace {
+        let removed = db::delete_corpus(&pool, &options.corpus).await.map_err(error)?;
+        println!("Removed {removed} examples from `{}`", options.corpus);
+    }
+
+    let mut new = Vec::new();
+    let mut seen = HashSet::new();
+    for (content, label) in examples {
+        let hash = hash_content(&content);
+        if !seen.insert(hash.clone()) {
+            continue;
+        }
+        if !db::reference_exists(&pool, &options.corpus, &hash, &endpoint.model).await.map_err(error)? {
+            new.push((content, label, hash));
+        }
+    }
+    println!("Embedding {} new examples with {}", new.len(), endpoint.model);
+
+    let client = Client::new();
+    let (mut ai, mut human) = (0, 0);
+    for batch in new.chunks(BATCH) {
+        // Embedded as the detector embeds posts
+        let texts: Vec<String> = batch.iter().map(|(content, _, _)| entities::strip(content)).collect();
+        let vectors = embeddings::embed(&client, &endpoint, &api_key, &texts)
+            .await
+            .map_err(|e| match e {
+                AppError::LlmApi(msg) => msg,
+                other => format!("{other:?}"),
+            })?;
+        for ((content, label, hash), vector) in batch.iter().zip(vectors) {
+            let bytes = embeddings::to_bytes(&vector);
+            db::insert_reference(&pool, &options.corpus, label, content, hash, &endpoint.model, &bytes)
+                .await
+                .map_err(error)?;
+            if label == "ai" {
+                ai += 1;
+            } else {
+                human += 1;
+            }
+        }
+    }
+    println!("Imported {ai} AI and {human} human examples into `{}`", options.corpus);
+    Ok(())
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut jsonl = None;
+    let mut corpus = None;
+    let mut label = None;
+    let mut replace = false;
+    let mut db = None;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| format!("{flag} needs a value\n{USAGE}"));
+        match flag.as_str() {
+            "--jsonl" => jsonl = Some(PathBuf::from(value()?)),
+            "--corpus" => corpus = Some(value()?),
+            "--label" => {
+                let value = value()?;
+                if value != "ai" && value != "human" {
+                    return Err("--label must be `ai` or `human`".to_string());
+                }
+                label = Some(value)
+            }
+            "--replace" => replace = true,
+            "--db" => db = Some(value()?),
+            other => return Err(format!("Unknown argument `{other}`\n{USAGE}")),
+        }
+    }
+    Ok(Options {
+        jsonl: jsonl.ok_or_else(|| format!("--jsonl is required\n{USAGE}"))?,
+        corpus: corpus.ok_or_else(|| format!("--corpus is required\n{USAGE}"))?,
+        label,
+        replace,
+        db,
+    })
+}
@@ -0,0 +1,59 @@
+use axum::middleware;
+use axum::routing::{get, post};
+use axum::Router;
+use reqwest::Client;
+use sqlx::SqlitePool;
+use tower_http::cors::{Any, CorsLayer};
+use tower_http::trace::TraceLayer;
+
+pub mod auth;
+pub mod config;
+pub mod db;
+pub mod errors;
+pub mod import;
+pub mod models;
+pub mod routes;
+pub mod services;
+pub mod train;
+
+use config::Config;
+use services::ensemble::Ensemble;
+use services::rules::RulesStore;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: SqlitePool,
+    pub http_client: Client,
+    pub config: Config,
+    pub rules: RulesStore,
+    /// Detectors run on every analyzed post.
+    pub ensemble: Arc<Ensemble>,
+}
+
+/// The full API router with CORS, tracing and API-key auth applied.
+pub fn app(state: AppState) -> Router {
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any);
+
+    // Protected routes (require API key)
+    let protected = Router::new()
+        .route("/api/analyze", post(routes::analyze::analyze))
+        .route("/api/explain", post(routes::explain::explain))
+        .route("/api/history", get(routes::history::history))
+        .route("/api/authors", get(routes::history::authors))
+        .route("/api/templates", get(routes::templates::templates))
+        .route("/api/campaigns", get(routes::campaigns::campaigns))
+        .route("/api/campaigns/{id}", get(routes::campaigns::campaign))
+        .layer(middleware::from_fn(auth::require_api_key));
+
+    Router::new()
+        .route("/api/health", get(routes::health::health))
+        .merge(protected)
+        .layer(cors)
+        .layer(TraceLayer::new_for_http())
+        .layer(axum::Extension(state.config.clone()))
+        .with_state(state)
+}
@@ -1,27 +1,12 @@
-use axum::middleware;
-use axum::routing::{get, post};
-use axum::Router;
 use reqwest::Client;
-use sqlx::SqlitePool;
-use tower_http::cors::{Any, CorsLayer};
-use tower_http::trace::TraceLayer;
+use std::sync::Arc;
+use std::time::Duration;
 
-mod auth;
-mod config;
-mod db;
-mod errors;
-mod models;
-mod routes;
-mod services;
-
-use config::Config;
-
-#[derive(Clone)]
-pub struct AppState {
-    pub db: SqlitePool,
-    pub http_client: Client,
-    pub config: Config,
-}
+use aidetector_server::config::Config;
+use aidetector_server::services::campaigns;
+use aidetector_server::services::ensemble::Ensemble;
+use aidetector_server::services::rules::{self, Rules, RulesStore};
+use aidetector_server::{app, db, import, train, AppState};
 
 #[tokio::main]
 async fn main() {
@@ -34,36 +19,58 @@ async fn main() {
         )
         .init();
 
+    // `aidetector-server train ...` fits the classifier detector and exits
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("train") {
+        if let Err(e) = train::run(&args[2..]).await {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    // `aidetector-server import ...` loads a reference corpus for the embeddings detector and exits
+    if args.get(1).map(String::as_str) == Some("import") {
+        if let Err(e) = import::run(&args[2..]).await {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let config = Config::from_env();
     let pool = db::init_pool(&config.database_url).await;
     let http_client = Client::new();
 
+    let rules = Rules::load(config.rules_path.as_deref())
+        .unwrap_or_else(|e| panic!("Invalid heuristic rules: {e}"));
+    tracing::info!("Heuristic rules version: {}", rules.version);
+    let rules = RulesStore::new(rules);
+    if let Some(path) = &config.rules_path {
+        rules::spawn_watcher(
+            rules.clone(),
+            path.clone(),
+            Duration::from_secs(config.rules_reload_secs.max(1)),
+        );
+    }
+
+    if config.campaigns_interval_secs > 0 {
+        campaigns::spawn_job(
+            pool.clone(),
+            rules.clone(),
+            Duration::from_secs(config.campaigns_interval_secs),
+        );
+    }
+
+    let ensemble = Arc::new(Ensemble::from_config(&http_client, &config, &pool));
+
     let state = AppState {
         db: pool,
         http_client,
         config: config.clone(),
+        rules,
+        ensemble,
     };
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
-
-    // Protected routes (require API key)
-    let protected = Router::new()
-        .route("/api/analyze", post(routes::analyze::analyze))
-        .route("/api/history", get(routes::history::history))
-        .route("/api/authors", get(routes::history::authors))
-        .layer(middleware::from_fn(auth::require_api_key));
-
-    let app = Router::new()
-        .route("/api/health", get(routes::health::health))
-        .merge(protected)
-        .layer(cors)
-        .layer(TraceLayer::new_for_http())
-        .layer(axum::Extension(config.clone()))
-        .with_state(state);
-
     let addr = format!("0.0.0.0:{}", config.port);
     tracing::info!("Server starting on {addr}");
 
@@ -71,7 +78,7 @@ async fn main() {
         .await
         .expect("Failed to bind");
 
-    axum::serve(listener, app)
+    axum::serve(listener, app(state))
         .await
         .expect("Server failed");
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use crate::services::rules::Labels;
+
 #[derive(Debug, Deserialize)]
 pub struct AnalyzeRequest {
     pub content: String,
@@ -17,6 +19,10 @@ pub enum Platform {
     LinkedIn,
 }
 
+impl Platform {
+    pub const ALL: [Platform; 3] = [Platform::Twitter, Platform::Instagram, Platform::LinkedIn];
+}
+
 impl std::fmt::Display for Platform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -32,6 +38,10 @@ pub struct AnalyzeResponse {
     pub score: u8,
     pub confidence: f64,
     pub label: String,
+    /// Detected language (ISO 639-1), null when undetermined.
+    pub language: Option<String>,
+    /// Links, mentions, cashtags, hashtags and quoted text left out of scoring.
+    pub removed: Vec<Removed>,
     pub breakdown: Breakdown,
 }
 
@@ -39,7 +49,303 @@ pub struct AnalyzeResponse {
 pub struct Breakdown {
     pub llm_score: Option<u8>,
     pub heuristic_score: u8,
+    /// Score of the local transformer classifier, null when it isn't configured.
+    pub transformer_score: Option<u8>,
     pub signals: Vec<String>,
+    pub evidence: Vec<Evidence>,
+    pub contributions: Vec<Contribution>,
+    /// Each detector's score, confidence and ensemble weight.
+    pub detectors: Vec<DetectorResult>,
+    /// Watermark test of the best-matching known scheme, null when none are configured.
+    pub watermark: Option<WatermarkResult>,
+    /// Stored posts sharing a template with this one, null when it is too short to fingerprint.
+    pub near_duplicates: Option<NearDuplicates>,
+    /// Most similar labeled reference examples, null when no embeddings provider is configured.
+    pub neighbours: Option<Vec<Neighbour>>,
+}
+
+/// One detector's verdict as combined by the ensemble.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectorResult {
+    pub name: String,
+    pub score: u8,
+    pub confidence: f64,
+    pub weight: f64,
+    /// Why the detector failed; it was left out of the score. Null when it ran.
+    pub error: Option<String>,
+}
+
+/// Green-list watermark z-test. A detected watermark sets the final score to 10.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatermarkResult {
+    pub scheme: String,
+    pub detected: bool,
+    pub z_score: f64,
+    /// Tokens tested (distinct context/token pairs).
+    pub tokens: usize,
+    /// Tested tokens on the green list.
+    pub green: usize,
+}
+
+/// A labeled reference example close to the post in embedding space.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Neighbour {
+    pub id: String,
+    pub corpus: String,
+    /// `ai` or `human`.
+    pub label: String,
+    /// Cosine similarity of the embeddings, -1 to 1.
+    pub similarity: f64,
+    pub content_preview: String,
+}
+
+/// A stored reference example with its embedding, as loaded into the index.
+#[derive(Debug, FromRow)]
+pub struct ReferenceRecord {
+    pub id: String,
+    pub corpus: String,
+    pub label: String,
+    pub content_preview: String,
+    pub embedding: Vec<u8>,
+}
+
+/// Stored posts at least `templates.min_similarity` similar to a post (estimated Jaccard
+/// similarity of their word 3-shingles).
+#[derive(Debug, Clone, Serialize)]
+pub struct NearDuplicates {
+    pub count: usize,
+    /// Distinct authors of the near-duplicates.
+    pub authors: Vec<String>,
+    /// The closest few, exact copies first.
+    pub closest: Vec<NearDuplicate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NearDuplicate {
+    pub id: String,
+    pub author: Option<String>,
+    pub platform: String,
+    pub score: i32,
+    /// Estimated share of word 3-shingles in common, 0-1.
+    pub similarity: f64,
+    pub content_preview: String,
+    pub created_at: String,
+}
+
+/// A stored post's MinHash signature, for near-duplicate lookups.
+#[derive(Debug, FromRow)]
+pub struct Fingerprint {
+    pub id: String,
+    pub author: Option<String>,
+    pub platform: String,
+    pub score: i32,
+    pub minhash: Vec<u8>,
+    pub content_preview: String,
+    pub created_at: String,
+}
+
+/// Stored posts linked by near-duplicate signatures.
+#[derive(Debug, Serialize)]
+pub struct TemplateCluster {
+    pub size: usize,
+    pub authors: Vec<String>,
+    pub first_seen: String,
+    pub last_seen: String,
+    /// Newest first, with `similarity` to the newest.
+    pub posts: Vec<NearDuplicate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplatesQuery {
+    pub min_size: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplatesResponse {
+    pub clusters: Vec<TemplateCluster>,
+}
+
+/// A stored post scoring at least `campaigns.min_score`, as read by the campaign job.
+#[derive(Debug, Clone, FromRow)]
+pub struct FlaggedPost {
+    pub id: String,
+    pub author: String,
+    pub platform: String,
+    pub score: i32,
+    /// JSON array of signal names.
+    pub signals: String,
+    pub minhash: Option<Vec<u8>>,
+    pub content_preview: String,
+    pub created_at: String,
+}
+
+/// Authors posting alike AI-flagged posts close together in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campaign {
+    /// Id of the campaign's earliest post.
+    pub id: String,
+    /// Most posts first.
+    pub authors: Vec<CampaignAuthor>,
+    pub platforms: Vec<String>,
+    pub post_count: usize,
+    pub average_score: f64,
+    pub first_seen: String,
+    pub last_seen: String,
+    /// Near-duplicate groups among the campaign's posts, largest first.
+    pub templates: Vec<SharedTemplate>,
+    /// Post pairs linked as near-duplicates.
+    pub template_links: usize,
+    /// Post pairs linked by overlapping signals only.
+    pub structural_links: usize,
+    /// When the clustering run that found it finished.
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignAuthor {
+    pub author: String,
+    pub posts: usize,
+}
+
+/// A template posted by several campaign members.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedTemplate {
+    pub size: usize,
+    pub authors: Vec<String>,
+    /// Preview of the newest post using it.
+    pub content_preview: String,
+}
+
+/// One of a campaign's linked posts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampaignPost {
+    pub id: String,
+    pub author: String,
+    pub platform: String,
+    pub score: i32,
+    pub signals: Vec<String>,
+    pub content_preview: String,
+    pub created_at: String,
+}
+
+/// A campaign with its posts, oldest first.
+#[derive(Debug, Serialize)]
+pub struct CampaignDetail {
+    #[serde(flatten)]
+    pub campaign: Campaign,
+    pub posts: Vec<CampaignPost>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CampaignsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CampaignsResponse {
+    pub campaigns: Vec<Campaign>,
+    pub total: i64,
+}
+
+/// One vote in the heuristic weighted average. `share` is the number of score points
+/// this vote adds to the pre-floor average (`score * weight / total_weight`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contribution {
+    pub measurement: String,
+    pub signal: Option<String>,
+    pub value: Option<f64>,
+    pub score: f64,
+    pub weight: f64,
+    pub share: f64,
+}
+
+/// A hard floor whose measurement threshold was reached. `applied` is true when it
+/// actually raised the score.
+#[derive(Debug, Clone, Serialize)]
+pub struct FloorHit {
+    pub measurement: String,
+    pub score: u8,
+    pub applied: bool,
+}
+
+/// The heuristic score with one vote or floor taken out.
+#[derive(Debug, Serialize)]
+pub struct Counterfactual {
+    pub removed: String,
+    pub measurement: String,
+    pub signal: Option<String>,
+    pub score: u8,
+    pub delta: i32,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainResponse {
+    pub heuristic_score: u8,
+    pub raw_score: f64,
+    pub rules_version: String,
+    pub language: Option<String>,
+    pub removed: Vec<Removed>,
+    pub signals: Vec<String>,
+    pub contributions: Vec<Contribution>,
+    pub floors: Vec<FloorHit>,
+    pub counterfactuals: Vec<Counterfactual>,
+}
+
+/// A span of the analyzed content that triggered a signal.
+/// `start`/`end` are byte offsets, `char_start`/`char_end` are character offsets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evidence {
+    pub signal: String,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+impl Evidence {
+    /// `char_start` is the char offset of `span.start`, which callers with many
+    /// spans track incrementally rather than recounting from the start of `content`.
+    pub fn new(signal: &str, content: &str, span: std::ops::Range<usize>, char_start: usize) -> Self {
+        let char_end = char_start + content[span.clone()].chars().count();
+        Self {
+            signal: signal.to_string(),
+            text: content[span.clone()].to_string(),
+            start: span.start,
+            end: span.end,
+            char_start,
+            char_end,
+        }
+    }
+}
+
+/// A span of the analyzed content cut before scoring. `kind` is `url`, `mention`,
+/// `cashtag`, `hashtag` (only the `#` is cut from inline hashtags) or `quote`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Removed {
+    pub kind: String,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+impl Removed {
+    pub fn new(kind: &str, content: &str, span: std::ops::Range<usize>) -> Self {
+        let char_start = content[..span.start].chars().count();
+        Self {
+            kind: kind.to_string(),
+            text: content[span.clone()].to_string(),
+            start: span.start,
+            end: span.end,
+            char_start,
+            char_end: char_start + content[span].chars().count(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, FromRow)]
@@ -55,6 +361,15 @@ pub struct AnalysisRecord {
     pub llm_score: Option<i32>,
     pub heuristic_score: i32,
     pub signals: String,
+    pub evidence: String,
+    pub contributions: String,
+    pub language: Option<String>,
+    pub removed: String,
+    pub detectors: String,
+    pub watermark: Option<String>,
+    pub style: Option<String>,
+    pub minhash: Option<Vec<u8>>,
+    pub neighbours: Option<String>,
     pub created_at: String,
 }
 
@@ -85,27 +400,25 @@ pub struct HistoryItem {
     pub llm_score: Option<i32>,
     pub heuristic_score: i32,
     pub signals: String,
+    pub evidence: String,
+    pub contributions: String,
+    pub language: Option<String>,
+    pub removed: String,
+    pub detectors: String,
+    pub watermark: Option<String>,
     pub created_at: String,
 }
 
-pub fn score_to_label(score: u8, heuristics_only: bool) -> String {
-    if heuristics_only {
-        // Without LLM, the 4-5 range is genuinely uncertain (no second opinion)
+pub fn score_to_label(score: u8, heuristics_only: bool, labels: &Labels) -> String {
+    let label = match score {
+        s if s > 10 => "unknown",
+        s if s >= labels.ai => "ai",
+        s if s >= labels.likely_ai => "likely_ai",
+        // Without a second opinion, the middle range is genuinely uncertain
         // but strong signals at extremes are still definitive
-        match score {
-            0..=3 => "human".to_string(),
-            4..=5 => "uncertain".to_string(),
-            6..=7 => "likely_ai".to_string(),
-            8..=10 => "ai".to_string(),
-            _ => "unknown".to_string(),
-        }
-    } else {
-        match score {
-            0..=3 => "human".to_string(),
-            4..=5 => "mixed".to_string(),
-            6..=7 => "likely_ai".to_string(),
-            8..=10 => "ai".to_string(),
-            _ => "unknown".to_string(),
-        }
-    }
+        s if s >= labels.mixed && heuristics_only => "uncertain",
+        s if s >= labels.mixed => "mixed",
+        _ => "human",
+    };
+    label.to_string()
 }
@@ -10,6 +10,20 @@ pub async fn analyze(
     State(state): State<AppState>,
     Json(request): Json<AnalyzeRequest>,
 ) -> Result<Json<AnalyzeResponse>, AppError> {
+    validate_content(&request)?;
+
+    let response = detector::analyze(
+        &state.db,
+        &state.ensemble,
+        state.rules.current(),
+        &request,
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+pub fn validate_content(request: &AnalyzeRequest) -> Result<(), AppError> {
     if request.content.trim().is_empty() {
         return Err(AppError::BadRequest("Content cannot be empty".to_string()));
     }
@@ -18,7 +32,5 @@ pub async fn analyze(
         return Err(AppError::BadRequest("Content too long (max 50000 chars)".to_string()));
     }
 
-    let response = detector::analyze(&state.db, &state.http_client, &state.config, &request).await?;
-
-    Ok(Json(response))
+    Ok(())
 }
@@ -0,0 +1,29 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+
+use crate::db;
+use crate::errors::AppError;
+use crate::models::{CampaignDetail, CampaignsQuery, CampaignsResponse};
+use crate::AppState;
+
+pub async fn campaigns(
+    State(state): State<AppState>,
+    Query(query): Query<CampaignsQuery>,
+) -> Result<Json<CampaignsResponse>, AppError> {
+    let limit = query.limit.unwrap_or(20).min(100);
+    let offset = query.offset.unwrap_or(0);
+
+    let (campaigns, total) = db::get_campaigns(&state.db, limit, offset).await?;
+
+    Ok(Json(CampaignsResponse { campaigns, total }))
+}
+
+pub async fn campaign(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<CampaignDetail>, AppError> {
+    db::get_campaign(&state.db, &id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("No campaign {id}")))
+}
@@ -0,0 +1,19 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::errors::AppError;
+use crate::models::{AnalyzeRequest, ExplainResponse};
+use crate::routes::analyze::validate_content;
+use crate::services::detector;
+use crate::AppState;
+
+pub async fn explain(
+    State(state): State<AppState>,
+    Json(request): Json<AnalyzeRequest>,
+) -> Result<Json<ExplainResponse>, AppError> {
+    validate_content(&request)?;
+
+    let response = detector::explain(&state.db, state.rules.current(), &request).await?;
+
+    Ok(Json(response))
+}
@@ -1,13 +1,19 @@
-use axum::extract::Extension;
+use axum::extract::State;
 use axum::Json;
 use serde_json::{json, Value};
 
-use crate::config::{Config, LlmProvider};
+use crate::config::LlmProvider;
+use crate::AppState;
 
-pub async fn health(Extension(config): Extension<Config>) -> Json<Value> {
+pub async fn health(State(state): State<AppState>) -> Json<Value> {
+    let config = &state.config;
     let (provider, model): (&str, Option<&str>) = match &config.llm_provider {
         LlmProvider::Anthropic => ("anthropic", Some(config.anthropic_model.as_str())),
         LlmProvider::OpenRouter => ("openrouter", Some(config.openrouter_model.as_str())),
+        LlmProvider::Transformer => (
+            "transformer",
+            config.transformer_model_path.as_deref().and_then(|p| p.to_str()),
+        ),
         LlmProvider::None => ("none", None),
     };
 
@@ -15,6 +21,7 @@ pub async fn health(Extension(config): Extension<Config>) -> Json<Value> {
         "status": "ok",
         "version": env!("CARGO_PKG_VERSION"),
         "provider": provider,
-        "model": model
+        "model": model,
+        "rules_version": state.rules.current().version
     }))
 }
@@ -1,3 +1,6 @@
 pub mod analyze;
+pub mod campaigns;
+pub mod explain;
 pub mod health;
 pub mod history;
+pub mod templates;
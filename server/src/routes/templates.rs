@@ -0,0 +1,33 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use chrono::Utc;
+
+use crate::db;
+use crate::errors::AppError;
+use crate::models::{TemplatesQuery, TemplatesResponse};
+use crate::services::templates;
+use crate::AppState;
+
+/// Most recent posts clustered per request, whatever the lookback.
+const MAX_POSTS: i64 = 5000;
+
+pub async fn templates(
+    State(state): State<AppState>,
+    Query(query): Query<TemplatesQuery>,
+) -> Result<Json<TemplatesResponse>, AppError> {
+    let min_size = query.min_size.unwrap_or(3).max(2);
+    let limit = query.limit.unwrap_or(20).min(100);
+
+    let rules = state.rules.current();
+    let since = (Utc::now() - chrono::Duration::days(rules.templates.lookback_days))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    let posts = db::get_colliding_fingerprints(&state.db, &since, MAX_POSTS).await?;
+    let min_similarity = rules.templates.min_similarity;
+    let mut clusters = tokio::task::spawn_blocking(move || templates::clusters(&posts, min_similarity, min_size))
+        .await
+        .map_err(|e| AppError::Internal(format!("Template clustering panicked: {e}")))?;
+    clusters.truncate(limit);
+
+    Ok(Json(TemplatesResponse { clusters }))
+}
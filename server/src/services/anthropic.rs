@@ -1,9 +1,38 @@
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
 use crate::errors::AppError;
-use crate::services::detector::{LlmResult, SYSTEM_PROMPT, parse_score};
+use crate::services::detector::{LlmResult, parse_score};
+use crate::services::ensemble::{Detector, Input, Verdict};
+
+/// The Anthropic Messages API as an ensemble detector.
+pub struct Anthropic {
+    client: Client,
+    config: Config,
+}
+
+impl Anthropic {
+    pub fn new(client: &Client, config: &Config) -> Self {
+        Self {
+            client: client.clone(),
+            config: config.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Detector for Anthropic {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let llm = analyze(&self.client, &self.config, &input.system_prompt(), &input.stripped()).await?;
+        Ok(llm.into())
+    }
+}
 
 #[derive(Serialize)]
 struct MessagesRequest {
@@ -30,7 +59,7 @@ struct ContentBlock {
     text: String,
 }
 
-pub async fn analyze(client: &Client, config: &Config, text: &str) -> Result<LlmResult, AppError> {
+pub async fn analyze(client: &Client, config: &Config, system_prompt: &str, text: &str) -> Result<LlmResult, AppError> {
     if config.anthropic_api_key.is_empty() {
         return Err(AppError::LlmApi(
             "Anthropic token not configured. Set ANTHROPIC_API_KEY or ANTHROPIC_MAX_SETUP_TOKEN".to_string(),
@@ -39,7 +68,7 @@ pub async fn analyze(client: &Client, config: &Config, text: &str) -> Result<Llm
 
     let request = MessagesRequest {
         model: config.anthropic_model.clone(),
-        system: SYSTEM_PROMPT.to_string(),
+        system: system_prompt.to_string(),
         messages: vec![Message {
             role: "user".to_string(),
             content: format!("Analyze this text for AI generation:\n\n{text}"),
@@ -0,0 +1,226 @@
+//! Per-author stylometric baselines.
+//!
+//! Every analysis stores a small style profile of the post (sentence length,
+//! punctuation habits, dash, slang and AI-vocabulary rates, word length) and
+//! the hard floors it reached. Before a post is scored, the author's recent
+//! profiles on the same platform are summarized into a baseline, and the post
+//! is measured by how far it moved from it toward AI writing: the root mean
+//! square of the z-scores of longer sentences, more dashes, more AI vocabulary
+//! and less slang (`author_deviation`). An account that suddenly switches to
+//! ghostwritten posts moves far from its own history even when each post looks
+//! plausible alone, while a formal writer dashing off a casual post does not
+//! count as a shift. Floors the author reaches in most of their history (an
+//! author who has always typed em dashes) are habits, not evidence, and can be
+//! waived by the rules.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::services::rules::Rules;
+use crate::services::stats::TextStats;
+
+/// Distance of a post from its author's baseline, registered in `rules::MEASUREMENTS`.
+pub const MEASUREMENTS: &[&str] = &["author_deviation"];
+
+/// Style features the deviation is measured on, with the direction a shift
+/// toward AI writing moves them (1 up, -1 down) and the smallest spread a
+/// baseline assumes for them, so a history of near-identical posts doesn't turn
+/// tiny differences into huge z-scores. The other features are profile only.
+const FEATURES: &[(&str, f64, f64)] = &[
+    ("sentence_length", 1.0, 2.0),
+    ("dash_rate", 1.0, 0.5),
+    ("slang_rate", -1.0, 0.5),
+    ("ai_vocabulary_rate", 1.0, 0.5),
+];
+
+/// Below this many words, rates are noise and no features are taken.
+const MIN_WORDS: usize = 5;
+
+/// Features both the post and the baseline must have before a deviation is measured.
+const MIN_FEATURES: usize = 3;
+
+/// One post's style, as stored with its analysis.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Style {
+    /// Feature name -> value; features that couldn't be measured are absent.
+    pub features: BTreeMap<String, f64>,
+    /// Measurements whose hard floor the post reached, waived or not.
+    #[serde(default)]
+    pub floors: Vec<String>,
+}
+
+/// Style features of the post in `stats`, with counts taken from the heuristic `measured` values.
+pub fn features(stats: &TextStats, measured: &[(&str, f64)]) -> BTreeMap<String, f64> {
+    let mut out = BTreeMap::new();
+    let words = stats.word_count();
+    if words < MIN_WORDS {
+        return out;
+    }
+    let value = |name: &str| measured.iter().find(|(n, _)| *n == name).map_or(0.0, |(_, v)| *v);
+    let per_100_words = |count: f64| count * 100.0 / words as f64;
+
+    let sentences = stats.sentences.len();
+    if sentences > 0 {
+        let ending = |terminators: &[char]| {
+            stats.sentences.iter().filter(|s| s.terminator.is_some_and(|t| terminators.contains(&t))).count() as f64
+                / sentences as f64
+        };
+        out.insert("sentence_length".to_string(), words as f64 / sentences as f64);
+        out.insert("comma_rate".to_string(), stats.commas as f64 / sentences as f64);
+        out.insert("exclamation_share".to_string(), ending(&['!', '！']));
+        out.insert("question_share".to_string(), ending(&['?', '？']));
+    }
+    out.insert("dash_rate".to_string(), per_100_words(value("em_en_dash") + value("spaced_hyphen")));
+    out.insert("slang_rate".to_string(), per_100_words(value("informality")));
+    out.insert("ai_vocabulary_rate".to_string(), per_100_words(value("ai_vocabulary")));
+    let letters: usize = stats.words.iter().map(|(_, w)| w.chars().count()).sum();
+    out.insert("mean_word_length".to_string(), letters as f64 / words as f64);
+    out
+}
+
+/// Measurements of the rules' hard floors that `measured` reached.
+pub fn floors_reached(rules: &Rules, measured: &[(&str, f64)]) -> Vec<String> {
+    let mut out: Vec<String> = rules
+        .floors
+        .iter()
+        .filter(|floor| measured.iter().any(|(name, value)| *name == floor.measurement && *value >= floor.at_least))
+        .map(|floor| floor.measurement.clone())
+        .collect();
+    out.dedup();
+    out
+}
+
+/// Mean and spread of an author's past styles.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    /// Past posts summarized.
+    pub posts: usize,
+    /// Feature -> (mean, standard deviation, posts that had it).
+    features: BTreeMap<String, (f64, f64, usize)>,
+    /// Floor measurement -> share of posts that reached it.
+    floors: BTreeMap<String, f64>,
+}
+
+impl Baseline {
+    pub fn new(history: &[Style]) -> Self {
+        let mut values: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        let mut floors: BTreeMap<String, f64> = BTreeMap::new();
+        for style in history {
+            for (name, value) in &style.features {
+                values.entry(name).or_default().push(*value);
+            }
+            for floor in &style.floors {
+                *floors.entry(floor.clone()).or_default() += 1.0;
+            }
+        }
+        let features = values
+            .into_iter()
+            .map(|(name, values)| {
+                let n = values.len() as f64;
+                let mean = values.iter().sum::<f64>() / n;
+                let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+                (name.to_string(), (mean, variance.sqrt(), values.len()))
+            })
+            .collect();
+        for share in floors.values_mut() {
+            *share /= history.len() as f64;
+        }
+        Self {
+            posts: history.len(),
+            features,
+            floors,
+        }
+    }
+
+    /// Root mean square z-score of `features` against the baseline, over the
+    /// features the baseline saw in at least `min_posts` posts. Only shifts
+    /// toward AI writing count; a feature that moved the other way scores 0.
+    /// None when too few features compare.
+    pub fn deviation(&self, features: &BTreeMap<String, f64>, min_posts: usize) -> Option<f64> {
+        let z: Vec<f64> = FEATURES
+            .iter()
+            .filter_map(|(name, direction, min_spread)| {
+                let value = features.get(*name)?;
+                let (mean, spread, n) = self.features.get(*name)?;
+                (*n >= min_posts).then(|| (direction * (value - mean) / spread.max(*min_spread)).max(0.0))
+            })
+            .collect();
+        (z.len() >= MIN_FEATURES).then(|| (z.iter().map(|z| z * z).sum::<f64>() / z.len() as f64).sqrt())
+    }
+
+    /// Floors in `waivable` this author reaches in at least `habitual` of their posts.
+    pub fn habits(&self, waivable: &[String], habitual: f64) -> Vec<String> {
+        waivable
+            .iter()
+            .filter(|m| self.floors.get(*m).is_some_and(|share| *share >= habitual))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(text: &str, measured: &[(&str, f64)]) -> Style {
+        Style {
+            features: features(&TextStats::new(text), measured),
+            floors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_deviation_from_history() {
+        let casual = [
+            "lol yeah i tried that last week, total mess. never again tbh",
+            "ok so the game last night?? absolutely unreal. still buzzing",
+            "anyone else get the update yet, mine keeps crashing lol",
+            "honestly the best pizza in town is the place by the station",
+            "cant believe its friday already. long week, need sleep",
+        ];
+        let history: Vec<Style> = casual.iter().map(|t| style(t, &[("informality", 2.0)])).collect();
+        let baseline = Baseline::new(&history);
+        assert_eq!(baseline.posts, 5);
+
+        let same = style("yeah the new place downtown is great, went twice already lol", &[("informality", 2.0)]);
+        let ghostwritten = style(
+            "In today's rapidly evolving landscape, leveraging comprehensive frameworks \
+             — grounded in robust, data-driven insights — empowers organizations to unlock \
+             transformative growth, foster meaningful collaboration, and navigate complexity.",
+            &[("em_en_dash", 2.0), ("ai_vocabulary", 6.0)],
+        );
+        let near = baseline.deviation(&same.features, 5).unwrap();
+        let far = baseline.deviation(&ghostwritten.features, 5).unwrap();
+        assert!(near < 1.5, "{near}");
+        assert!(far > 3.0, "{far}");
+
+        // Casual posts from a formal writer are no shift toward AI
+        let formal = [
+            "We are pleased to announce the completion of the quarterly infrastructure review.",
+            "The committee has approved the revised budget for the upcoming fiscal year.",
+            "Please find attached the minutes of yesterday's meeting for your reference.",
+            "Our team will present the updated roadmap at the next scheduled review session.",
+            "The office will remain closed on Monday in observance of the national holiday.",
+        ];
+        let history: Vec<Style> = formal.iter().map(|t| style(t, &[])).collect();
+        let casual = style("lol ok cant make it tonight, my car died again. ugh. next week?", &[("informality", 3.0)]);
+        let shift = Baseline::new(&history).deviation(&casual.features, 5).unwrap();
+        assert!(shift < 0.5, "{shift}");
+
+        // Not enough history for any feature
+        assert!(baseline.deviation(&same.features, 6).is_none());
+        assert!(style("too short", &[]).features.is_empty());
+    }
+
+    #[test]
+    fn test_habitual_floors() {
+        let dashes = Style {
+            features: BTreeMap::new(),
+            floors: vec!["em_en_dash".to_string()],
+        };
+        let baseline = Baseline::new(&[dashes.clone(), dashes, Style::default()]);
+        let waivable = ["em_en_dash".to_string(), "spaced_hyphen".to_string()];
+        assert_eq!(baseline.habits(&waivable, 0.5), ["em_en_dash"]);
+        assert!(baseline.habits(&waivable, 0.8).is_empty());
+    }
+}
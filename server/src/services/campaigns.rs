@@ -0,0 +1,356 @@
+//! Coordinated campaigns: groups of authors posting alike AI-flagged posts
+//! close together in time.
+//!
+//! A background job periodically reads the last `campaigns.lookback_days` of
+//! stored posts scoring at least `campaigns.min_score`. Two posts by different
+//! authors at most `window_minutes` apart are linked when they are
+//! near-duplicates (the MinHash signatures of `templates`) or, on the same
+//! platform, when their heuristic signal sets overlap by `min_signal_overlap`:
+//! one prompt run through many accounts leaves the same marks even when the
+//! wording differs. Authors joined by links form a campaign once there are
+//! `min_authors` of them. Each run replaces the stored campaigns.
+
+use chrono::{NaiveDateTime, Utc};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::db;
+use crate::models::{Campaign, CampaignAuthor, CampaignDetail, CampaignPost, Fingerprint, FlaggedPost, SharedTemplate};
+use crate::services::rules::{CampaignRules, Rules, RulesStore};
+use crate::services::templates;
+
+/// Format of `created_at` in the analyses table.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Shared templates listed per campaign.
+const TEMPLATES: usize = 10;
+
+struct Post<'a> {
+    post: &'a FlaggedPost,
+    time: NaiveDateTime,
+    signals: BTreeSet<String>,
+    signature: Option<Vec<u32>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Link {
+    Template,
+    Structure,
+}
+
+/// Jaccard overlap of two signal sets.
+fn overlap(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let shared = a.intersection(b).count();
+    let all = a.len() + b.len() - shared;
+    if all == 0 {
+        return 0.0;
+    }
+    shared as f64 / all as f64
+}
+
+/// How two posts by different authors are linked, if at all. Time is checked by the caller.
+fn link(a: &Post, b: &Post, rules: &CampaignRules, min_similarity: f64) -> Option<Link> {
+    if a.post.author == b.post.author {
+        return None;
+    }
+    if let (Some(x), Some(y)) = (&a.signature, &b.signature) {
+        if templates::similarity(x, y) >= min_similarity {
+            return Some(Link::Template);
+        }
+    }
+    let structural = a.post.platform == b.post.platform
+        && a.signals.len() >= rules.min_signals
+        && b.signals.len() >= rules.min_signals
+        && overlap(&a.signals, &b.signals) >= rules.min_signal_overlap;
+    structural.then_some(Link::Structure)
+}
+
+/// Campaigns among `posts`, most authors first. Posts near-duplicate at
+/// `min_similarity` count as one template.
+pub fn find(posts: &[FlaggedPost], rules: &CampaignRules, min_similarity: f64, updated_at: &str) -> Vec<CampaignDetail> {
+    let mut posts: Vec<Post> = posts
+        .iter()
+        .filter_map(|post| {
+            Some(Post {
+                post,
+                time: NaiveDateTime::parse_from_str(&post.created_at, TIME_FORMAT).ok()?,
+                signals: serde_json::from_str(&post.signals).unwrap_or_default(),
+                signature: post.minhash.as_deref().map(templates::from_bytes),
+            })
+        })
+        .collect();
+    posts.sort_by_key(|p| p.time);
+
+    // Authors are joined through links between their posts
+    let authors: BTreeSet<&str> = posts.iter().map(|p| p.post.author.as_str()).collect();
+    let index: HashMap<&str, usize> = authors.iter().enumerate().map(|(i, a)| (*a, i)).collect();
+    let mut parent: Vec<usize> = (0..index.len()).collect();
+    let window = chrono::Duration::minutes(rules.window_minutes);
+    let mut links = Vec::new();
+    for (i, a) in posts.iter().enumerate() {
+        for (j, b) in posts.iter().enumerate().skip(i + 1) {
+            if b.time - a.time > window {
+                break;
+            }
+            if let Some(kind) = link(a, b, rules, min_similarity) {
+                let x = templates::root(&mut parent, index[a.post.author.as_str()]);
+                let y = templates::root(&mut parent, index[b.post.author.as_str()]);
+                parent[x] = y;
+                links.push((i, j, kind));
+            }
+        }
+    }
+
+    // Linked posts and link counts per group of authors
+    let mut groups: BTreeMap<usize, (BTreeSet<usize>, usize, usize)> = BTreeMap::new();
+    for (i, j, kind) in links {
+        let group = groups
+            .entry(templates::root(&mut parent, index[posts[i].post.author.as_str()]))
+            .or_default();
+        group.0.extend([i, j]);
+        match kind {
+            Link::Template => group.1 += 1,
+            Link::Structure => group.2 += 1,
+        }
+    }
+
+    let mut out: Vec<CampaignDetail> = groups
+        .into_values()
+        .filter_map(|(members, template_links, structural_links)| {
+            // Indices ascend with time
+            let members: Vec<&Post> = members.into_iter().map(|i| &posts[i]).collect();
+            let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for p in &members {
+                *counts.entry(p.post.author.as_str()).or_default() += 1;
+            }
+            if counts.len() < rules.min_authors {
+                return None;
+            }
+            let mut authors: Vec<CampaignAuthor> = counts
+                .into_iter()
+                .map(|(author, posts)| CampaignAuthor {
+                    author: author.to_string(),
+                    posts,
+                })
+                .collect();
+            authors.sort_by_key(|a| std::cmp::Reverse(a.posts));
+
+            let average = members.iter().map(|p| p.post.score as f64).sum::<f64>() / members.len() as f64;
+            let campaign = Campaign {
+                id: members[0].post.id.clone(),
+                authors,
+                platforms: members.iter().map(|p| p.post.platform.clone()).collect::<BTreeSet<_>>().into_iter().collect(),
+                post_count: members.len(),
+                average_score: (average * 100.0).round() / 100.0,
+                first_seen: members[0].post.created_at.clone(),
+                last_seen: members[members.len() - 1].post.created_at.clone(),
+                templates: shared_templates(&members, min_similarity),
+                template_links,
+                structural_links,
+                updated_at: updated_at.to_string(),
+            };
+            let posts = members
+                .iter()
+                .map(|p| CampaignPost {
+                    id: p.post.id.clone(),
+                    author: p.post.author.clone(),
+                    platform: p.post.platform.clone(),
+                    score: p.post.score,
+                    signals: p.signals.iter().cloned().collect(),
+                    content_preview: p.post.content_preview.clone(),
+                    created_at: p.post.created_at.clone(),
+                })
+                .collect();
+            Some(CampaignDetail { campaign, posts })
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.campaign
+            .authors
+            .len()
+            .cmp(&a.campaign.authors.len())
+            .then_with(|| b.campaign.last_seen.cmp(&a.campaign.last_seen))
+    });
+    out
+}
+
+/// Near-duplicate groups of two or more among a campaign's posts.
+fn shared_templates(members: &[&Post], min_similarity: f64) -> Vec<SharedTemplate> {
+    let fingerprints: Vec<Fingerprint> = members
+        .iter()
+        .filter_map(|p| {
+            Some(Fingerprint {
+                id: p.post.id.clone(),
+                author: Some(p.post.author.clone()),
+                platform: p.post.platform.clone(),
+                score: p.post.score,
+                minhash: p.post.minhash.clone()?,
+                content_preview: p.post.content_preview.clone(),
+                created_at: p.post.created_at.clone(),
+            })
+        })
+        .collect();
+    templates::clusters(&fingerprints, min_similarity, 2)
+        .into_iter()
+        .take(TEMPLATES)
+        .map(|cluster| SharedTemplate {
+            size: cluster.size,
+            authors: cluster.authors,
+            content_preview: cluster.posts[0].content_preview.clone(),
+        })
+        .collect()
+}
+
+/// Cluster the lookback window and replace the stored campaigns. Returns how many were found.
+pub async fn run(pool: &SqlitePool, rules: Arc<Rules>) -> Result<usize, String> {
+    let now = Utc::now();
+    let since = (now - chrono::Duration::days(rules.campaigns.lookback_days)).format(TIME_FORMAT).to_string();
+    let posts = db::get_flagged_posts(pool, rules.campaigns.min_score, &since)
+        .await
+        .map_err(|e| e.to_string())?;
+    let updated_at = now.format(TIME_FORMAT).to_string();
+    let found = tokio::task::spawn_blocking(move || {
+        find(&posts, &rules.campaigns, rules.templates.min_similarity, &updated_at)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    db::replace_campaigns(pool, &found).await.map_err(|e| e.to_string())?;
+    Ok(found.len())
+}
+
+/// Run the clustering job now and every `interval` after, with the rules current at each run.
+pub fn spawn_job(pool: SqlitePool, rules: RulesStore, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match run(&pool, rules.current()).await {
+                Ok(found) => tracing::debug!("Campaign clustering found {found} campaigns"),
+                Err(e) => tracing::warn!("Campaign clustering failed: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AnalyzeRequest, Platform};
+    use crate::services::detector;
+    use crate::services::ensemble::Ensemble;
+    use crate::services::heuristics::Heuristics;
+
+    const TEMPLATE: &str = "Excited to announce that I have joined Acme as a senior engineer! \
+                            Grateful for the journey so far and can't wait to build the future \
+                            of payments with this incredible team.";
+
+    fn rules() -> CampaignRules {
+        Rules::builtin().campaigns.clone()
+    }
+
+    fn post(id: &str, author: &str, content: Option<&str>, signals: &[&str], created_at: &str) -> FlaggedPost {
+        FlaggedPost {
+            id: id.to_string(),
+            author: author.to_string(),
+            platform: "linkedin".to_string(),
+            score: 8,
+            signals: serde_json::to_string(signals).unwrap(),
+            minhash: content.and_then(templates::signature).map(|s| templates::to_bytes(&s)),
+            content_preview: content.unwrap_or_default().chars().take(150).collect(),
+            created_at: created_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_template_campaign() {
+        let edited = TEMPLATE.replace("senior engineer", "product manager");
+        let posts = [
+            post("1", "alice", Some(TEMPLATE), &[], "2026-03-01 10:00:00"),
+            post("2", "bob", Some(&edited), &[], "2026-03-01 10:20:00"),
+            post("3", "carol", Some(TEMPLATE), &[], "2026-03-01 10:50:00"),
+            // Same template, but hours later
+            post("4", "dave", Some(TEMPLATE), &[], "2026-03-01 14:00:00"),
+            // Same author as a member: not a link, not a member post
+            post("5", "alice", Some(TEMPLATE), &[], "2026-03-01 10:05:00"),
+        ];
+        let found = find(&posts, &rules(), 0.7, "2026-03-02 00:00:00");
+        assert_eq!(found.len(), 1);
+        let campaign = &found[0].campaign;
+        assert_eq!(campaign.id, "1");
+        let authors: Vec<&str> = campaign.authors.iter().map(|a| a.author.as_str()).collect();
+        assert_eq!(authors, ["alice", "bob", "carol"]);
+        assert_eq!(campaign.authors[0].posts, 2);
+        assert_eq!(campaign.post_count, 4);
+        assert_eq!((campaign.first_seen.as_str(), campaign.last_seen.as_str()), ("2026-03-01 10:00:00", "2026-03-01 10:50:00"));
+        assert_eq!(campaign.average_score, 8.0);
+        assert_eq!(campaign.structural_links, 0);
+        assert_eq!(campaign.templates.len(), 1);
+        assert_eq!(campaign.templates[0].size, 4);
+        assert_eq!(found[0].posts.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), ["1", "5", "2", "3"]);
+
+        // Two authors are not a campaign
+        assert!(find(&posts[..2], &rules(), 0.7, "").is_empty());
+    }
+
+    #[test]
+    fn test_structural_campaign() {
+        let marks = ["em_en_dash", "formulaic_phrases", "ai_vocabulary", "hook_body_cta", "emoji_bullets"];
+        let posts = [
+            post("1", "alice", None, &marks, "2026-03-01 10:00:00"),
+            post("2", "bob", None, &marks, "2026-03-01 10:30:00"),
+            post("3", "carol", None, &marks, "2026-03-01 11:00:00"),
+            // Too few signals to link
+            post("4", "dave", None, &marks[..3], "2026-03-01 10:10:00"),
+        ];
+        let found = find(&posts, &rules(), 0.7, "");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].campaign.authors.len(), 3);
+        assert_eq!(found[0].campaign.structural_links, 3);
+        assert!(found[0].campaign.templates.is_empty());
+
+        // One signal off in five is below the 0.8 overlap
+        let mut other = marks.to_vec();
+        other[4] = "title_case_hook";
+        let posts = [
+            post("1", "alice", None, &marks, "2026-03-01 10:00:00"),
+            post("2", "bob", None, &other, "2026-03-01 10:10:00"),
+            post("3", "carol", None, &marks, "2026-03-01 12:00:00"),
+        ];
+        assert!(find(&posts, &rules(), 0.7, "").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_copies_by_other_authors_are_stored_and_clustered() {
+        let path = std::env::temp_dir().join(format!("aidetector-campaigns-{}.db", uuid::Uuid::new_v4()));
+        let pool = db::init_pool(&format!("sqlite:{}", path.display())).await;
+        let mut rules = Rules::builtin();
+        rules.campaigns.min_score = 0;
+        let rules = Arc::new(rules);
+        let ensemble = Ensemble::new(vec![Box::new(Heuristics)]);
+
+        // Byte-identical copies from three accounts, the first one twice
+        for author in ["alice", "alice", "bob", "carol"] {
+            let request = AnalyzeRequest {
+                content: TEMPLATE.to_string(),
+                platform: Platform::LinkedIn,
+                post_id: None,
+                author: Some(author.to_string()),
+            };
+            detector::analyze(&pool, &ensemble, rules.clone(), &request).await.unwrap();
+        }
+        let since = "2000-01-01 00:00:00";
+        let posts = db::get_flagged_posts(&pool, 0, since).await.unwrap();
+        assert_eq!(posts.len(), 3, "the repeat by alice is served from the cache");
+        assert!(posts.iter().all(|p| p.minhash.is_some()));
+
+        assert_eq!(run(&pool, rules).await.unwrap(), 1);
+        let (campaigns, _) = db::get_campaigns(&pool, 10, 0).await.unwrap();
+        let authors: Vec<&str> = campaigns[0].authors.iter().map(|a| a.author.as_str()).collect();
+        assert_eq!(authors, ["alice", "bob", "carol"]);
+
+        pool.close().await;
+        let _ = std::fs::remove_file(path);
+    }
+}
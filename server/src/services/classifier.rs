@@ -0,0 +1,303 @@
+//! Trainable linear classifier over the heuristic measurements.
+//!
+//! The rules file weighs each signal by hand. This model learns the weights
+//! instead, from posts an LLM has already scored: `aidetector-server train`
+//! turns each post's heuristic measurements into a feature vector, fits a
+//! logistic regression or Gaussian naive Bayes model and writes it as JSON.
+//! Set `CLASSIFIER_PATH` to that file and the server runs it as a detector,
+//! which gives a second opinion without any LLM configured.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::errors::AppError;
+use crate::services::ensemble::{Detector, Input, Verdict};
+use crate::services::heuristics::{HeuristicResult, MEASUREMENTS};
+
+/// Gradient descent passes over the training set.
+const EPOCHS: usize = 500;
+
+const LEARNING_RATE: f64 = 0.5;
+
+/// L2 penalty on the logistic weights.
+const L2: f64 = 1e-3;
+
+/// Added to every naive Bayes variance, so constant features don't divide by zero.
+const VARIANCE_FLOOR: f64 = 1e-2;
+
+/// Feature vector of a heuristic result: every measurement in `MEASUREMENTS`
+/// order, 0 for measurements that weren't taken.
+pub fn features(result: &HeuristicResult) -> Vec<f64> {
+    MEASUREMENTS.iter().map(|name| measurement(result, name)).collect()
+}
+
+fn measurement(result: &HeuristicResult, name: &str) -> f64 {
+    result
+        .measurements
+        .iter()
+        .find(|(measured, _)| *measured == name)
+        .map_or(0.0, |(_, value)| *value)
+}
+
+/// One labelled post.
+pub struct Sample {
+    pub features: Vec<f64>,
+    pub ai: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Logistic,
+    NaiveBayes,
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "logistic" => Ok(Algorithm::Logistic),
+            "naive_bayes" => Ok(Algorithm::NaiveBayes),
+            other => Err(format!("Unknown model `{other}` (expected `logistic` or `naive_bayes`)")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Model {
+    /// Measurement names, in feature vector order.
+    pub features: Vec<String>,
+    /// Standardization applied before the model: `(x - mean) / scale`.
+    pub mean: Vec<f64>,
+    pub scale: Vec<f64>,
+    #[serde(flatten)]
+    pub kind: Kind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Kind {
+    Logistic { weights: Vec<f64>, bias: f64 },
+    NaiveBayes { human: Gaussian, ai: Gaussian },
+}
+
+/// Per-class feature distribution of a naive Bayes model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gaussian {
+    pub prior: f64,
+    pub mean: Vec<f64>,
+    pub variance: Vec<f64>,
+}
+
+impl Model {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let model: Model = serde_json::from_str(&data).map_err(|e| format!("Invalid model file: {e}"))?;
+        model.validate()?;
+        Ok(model)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(unknown) = self.features.iter().find(|f| !MEASUREMENTS.contains(&f.as_str())) {
+            return Err(format!("Model uses unknown measurement `{unknown}`"));
+        }
+        let n = self.features.len();
+        let lengths = match &self.kind {
+            Kind::Logistic { weights, .. } => vec![weights.len()],
+            Kind::NaiveBayes { human, ai } => vec![human.mean.len(), human.variance.len(), ai.mean.len(), ai.variance.len()],
+        };
+        if self.mean.len() != n || self.scale.len() != n || lengths.iter().any(|&l| l != n) {
+            return Err(format!("Model vectors must all have {n} entries, one per feature"));
+        }
+        Ok(())
+    }
+
+    /// Fit a model to `samples`, whose features follow `MEASUREMENTS`.
+    pub fn train(samples: &[Sample], algorithm: Algorithm) -> Result<Self, String> {
+        let ai = samples.iter().filter(|s| s.ai).count();
+        if ai == 0 || ai == samples.len() {
+            return Err("Training data needs both AI and human posts".to_string());
+        }
+
+        let n = MEASUREMENTS.len();
+        let rows = samples.len() as f64;
+        let mean: Vec<f64> = (0..n).map(|j| samples.iter().map(|s| s.features[j]).sum::<f64>() / rows).collect();
+        let scale: Vec<f64> = (0..n)
+            .map(|j| {
+                let variance = samples.iter().map(|s| (s.features[j] - mean[j]).powi(2)).sum::<f64>() / rows;
+                // Constant features are left unscaled
+                if variance > 0.0 { variance.sqrt() } else { 1.0 }
+            })
+            .collect();
+        let x: Vec<Vec<f64>> = samples.iter().map(|s| standardize(&s.features, &mean, &scale)).collect();
+        let y: Vec<bool> = samples.iter().map(|s| s.ai).collect();
+
+        let kind = match algorithm {
+            Algorithm::Logistic => fit_logistic(&x, &y),
+            Algorithm::NaiveBayes => Kind::NaiveBayes {
+                human: fit_gaussian(&x, &y, false),
+                ai: fit_gaussian(&x, &y, true),
+            },
+        };
+        Ok(Self {
+            features: MEASUREMENTS.iter().map(|m| m.to_string()).collect(),
+            mean,
+            scale,
+            kind,
+        })
+    }
+
+    /// Probability that the post behind `result` is AI-written.
+    pub fn probability(&self, result: &HeuristicResult) -> f64 {
+        let raw: Vec<f64> = self.features.iter().map(|name| measurement(result, name)).collect();
+        self.predict(&raw)
+    }
+
+    /// Probability of AI for a raw feature vector in `self.features` order.
+    pub fn predict(&self, raw: &[f64]) -> f64 {
+        let x = standardize(raw, &self.mean, &self.scale);
+        match &self.kind {
+            Kind::Logistic { weights, bias } => sigmoid(bias + dot(weights, &x)),
+            Kind::NaiveBayes { human, ai } => sigmoid(ai.log_likelihood(&x) - human.log_likelihood(&x)),
+        }
+    }
+}
+
+impl Gaussian {
+    fn log_likelihood(&self, x: &[f64]) -> f64 {
+        let features: f64 = x
+            .iter()
+            .zip(self.mean.iter().zip(&self.variance))
+            .map(|(v, (m, var))| -0.5 * ((v - m).powi(2) / var + (2.0 * std::f64::consts::PI * var).ln()))
+            .sum();
+        self.prior.ln() + features
+    }
+}
+
+fn fit_logistic(x: &[Vec<f64>], y: &[bool]) -> Kind {
+    let n = x[0].len();
+    let rows = x.len() as f64;
+    let mut weights = vec![0.0; n];
+    let mut bias = 0.0;
+    for _ in 0..EPOCHS {
+        let mut gradient = vec![0.0; n];
+        let mut bias_gradient = 0.0;
+        for (row, &label) in x.iter().zip(y) {
+            let error = sigmoid(bias + dot(&weights, row)) - label as u8 as f64;
+            for (g, v) in gradient.iter_mut().zip(row) {
+                *g += error * v;
+            }
+            bias_gradient += error;
+        }
+        for (w, g) in weights.iter_mut().zip(&gradient) {
+            *w -= LEARNING_RATE * (g / rows + L2 * *w);
+        }
+        bias -= LEARNING_RATE * bias_gradient / rows;
+    }
+    Kind::Logistic { weights, bias }
+}
+
+fn fit_gaussian(x: &[Vec<f64>], y: &[bool], class: bool) -> Gaussian {
+    let rows: Vec<&Vec<f64>> = x.iter().zip(y).filter(|(_, &label)| label == class).map(|(row, _)| row).collect();
+    let count = rows.len() as f64;
+    let n = x[0].len();
+    let mean: Vec<f64> = (0..n).map(|j| rows.iter().map(|r| r[j]).sum::<f64>() / count).collect();
+    let variance = (0..n)
+        .map(|j| rows.iter().map(|r| (r[j] - mean[j]).powi(2)).sum::<f64>() / count + VARIANCE_FLOOR)
+        .collect();
+    Gaussian {
+        prior: count / x.len() as f64,
+        mean,
+        variance,
+    }
+}
+
+fn standardize(raw: &[f64], mean: &[f64], scale: &[f64]) -> Vec<f64> {
+    raw.iter().zip(mean.iter().zip(scale)).map(|(v, (m, s))| (v - m) / s).collect()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// A trained model as an ensemble detector.
+pub struct Classifier {
+    model: Arc<Model>,
+}
+
+impl Classifier {
+    pub fn new(model: Model) -> Self {
+        Self { model: Arc::new(model) }
+    }
+}
+
+#[async_trait]
+impl Detector for Classifier {
+    fn name(&self) -> &'static str {
+        "classifier"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let analysis = input.analysis().await?;
+        Ok(Verdict::probability(self.model.probability(&analysis.result)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::heuristics;
+    use crate::services::rules::Rules;
+
+    /// Posts whose `ai_vocabulary` and `informality` counts separate the classes.
+    fn samples() -> Vec<Sample> {
+        let index = |name| MEASUREMENTS.iter().position(|m| *m == name).unwrap();
+        (0..40)
+            .map(|i| {
+                let ai = i % 2 == 0;
+                let mut features = vec![0.0; MEASUREMENTS.len()];
+                features[index("ai_vocabulary")] = if ai { 3.0 + (i % 3) as f64 } else { (i % 2) as f64 };
+                features[index("informality")] = if ai { 0.0 } else { 2.0 + (i % 4) as f64 };
+                features[index("word_count")] = 30.0 + i as f64;
+                Sample { features, ai }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_models_separate_training_data() {
+        let samples = samples();
+        for algorithm in [Algorithm::Logistic, Algorithm::NaiveBayes] {
+            let model = Model::train(&samples, algorithm).unwrap();
+            for s in &samples {
+                let p = model.predict(&s.features);
+                assert_eq!(p > 0.5, s.ai, "{algorithm:?} misclassified {:?} (p = {p})", s.features);
+            }
+        }
+        assert!(Model::train(&samples[..1], Algorithm::Logistic).is_err());
+    }
+
+    #[test]
+    fn test_model_file_round_trip() {
+        let model = Model::train(&samples(), Algorithm::Logistic).unwrap();
+        let json = serde_json::to_string(&model).unwrap();
+        assert!(json.contains("\"kind\":\"logistic\""));
+        let loaded: Model = serde_json::from_str(&json).unwrap();
+        loaded.validate().unwrap();
+
+        let result = heuristics::analyze("In today's world, we leverage synergy.", &Rules::builtin(), None);
+        assert_eq!(features(&result).len(), MEASUREMENTS.len());
+        assert_eq!(loaded.probability(&result), model.probability(&result));
+
+        let mut broken = loaded.clone();
+        broken.features[0] = "nope".to_string();
+        assert!(broken.validate().is_err());
+    }
+}
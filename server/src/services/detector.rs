@@ -1,13 +1,19 @@
-use reqwest::Client;
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
+use std::sync::Arc;
 
-use crate::config::{Config, LlmProvider};
 use crate::db;
 use crate::errors::AppError;
-use crate::models::{AnalysisRecord, AnalyzeRequest, AnalyzeResponse, Breakdown, score_to_label};
-use crate::services::{anthropic, heuristics, openrouter};
+use crate::models::{
+    AnalysisRecord, AnalyzeRequest, AnalyzeResponse, Breakdown, Contribution, DetectorResult, Evidence, ExplainResponse,
+    NearDuplicates, Removed, score_to_label,
+};
+use crate::services::baseline::{Baseline, Style};
+use crate::services::ensemble::{self, Ensemble, Input, Verdict};
+use crate::services::heuristics::{self, Context};
+use crate::services::templates;
+use crate::services::rules::Rules;
 
 #[derive(Debug)]
 pub struct LlmResult {
@@ -15,8 +21,20 @@ pub struct LlmResult {
     pub confidence: f64,
 }
 
+impl From<LlmResult> for Verdict {
+    fn from(llm: LlmResult) -> Self {
+        Self {
+            score: llm.score,
+            confidence: llm.confidence,
+            ..Default::default()
+        }
+    }
+}
+
 pub const SYSTEM_PROMPT: &str = r#"You are an AI content detection expert. Analyze the given text and determine how likely it is to be AI-generated.
 
+The text may be written in any language. The indicators below are English examples; look for their equivalents in the language of the text, and judge it against natural writing in that language.
+
 Score from 0-10:
 - 0-2: Clearly human-written (informal, typos, unique voice, personal anecdotes)
 - 3-4: Mostly human (some polished sections but overall natural)
@@ -47,6 +65,14 @@ Respond ONLY with valid JSON in this exact format:
 
 No other text. Just the JSON."#;
 
+/// The system prompt with the platform's additions, if any.
+pub fn system_prompt(platform_prompt: Option<&str>) -> String {
+    match platform_prompt {
+        Some(extra) => format!("{SYSTEM_PROMPT}\n\n{extra}"),
+        None => SYSTEM_PROMPT.to_string(),
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ScoreResponse {
     pub score: u8,
@@ -78,68 +104,130 @@ pub fn parse_score(content: &str) -> Result<LlmResult, AppError> {
 
 pub async fn analyze(
     pool: &SqlitePool,
-    client: &Client,
-    config: &Config,
+    ensemble: &Ensemble,
+    rules: Arc<Rules>,
     request: &AnalyzeRequest,
 ) -> Result<AnalyzeResponse, AppError> {
     let content_hash = hash_content(&request.content);
+    let platform = request.platform.to_string();
 
     // Check cache
-    if let Some(cached) = db::find_by_hash(pool, &content_hash).await {
+    if let Some(cached) = db::find_by_hash(pool, &content_hash, &platform, request.author.as_deref()).await {
         let signals: Vec<String> = serde_json::from_str(&cached.signals).unwrap_or_default();
+        let evidence: Vec<Evidence> = serde_json::from_str(&cached.evidence).unwrap_or_default();
+        let contributions: Vec<Contribution> =
+            serde_json::from_str(&cached.contributions).unwrap_or_default();
+        let removed: Vec<Removed> = serde_json::from_str(&cached.removed).unwrap_or_default();
+        let detectors: Vec<DetectorResult> = serde_json::from_str(&cached.detectors).unwrap_or_default();
+        let transformer_score = detectors.iter().find(|d| d.name == "transformer").map(|d| d.score);
+        let watermark = cached.watermark.as_deref().and_then(|w| serde_json::from_str(w).ok());
+        let neighbours = cached.neighbours.as_deref().and_then(|n| serde_json::from_str(n).ok());
+        let near_duplicates = match &cached.minhash {
+            Some(minhash) => {
+                let signature = templates::from_bytes(minhash);
+                let author = request.author.as_deref();
+                Some(near_duplicates(pool, &rules, &signature, author, Some(&cached.id)).await.0)
+            }
+            None => None,
+        };
         return Ok(AnalyzeResponse {
             score: cached.score as u8,
             confidence: cached.confidence,
             label: cached.label,
+            language: cached.language,
+            removed,
             breakdown: Breakdown {
                 llm_score: cached.llm_score.map(|s| s as u8),
                 heuristic_score: cached.heuristic_score as u8,
+                transformer_score,
                 signals,
+                evidence,
+                contributions,
+                detectors,
+                watermark,
+                near_duplicates,
+                neighbours,
             },
         });
     }
 
-    // Run heuristic analysis (always needed)
-    let heuristic_handle = {
-        let text = request.content.clone();
-        tokio::task::spawn_blocking(move || heuristics::analyze(&text))
-    };
-
-    // Run LLM analysis if a provider is configured
-    let llm_result = match config.llm_provider {
-        LlmProvider::Anthropic => Some(anthropic::analyze(client, config, &request.content).await?),
-        LlmProvider::OpenRouter => Some(openrouter::analyze(client, config, &request.content).await?),
-        LlmProvider::None => {
-            tracing::debug!("No LLM provider configured — using heuristics only");
-            None
+    // Near-duplicates by other accounts and the author's own history feed the heuristics
+    let signature = templates::signature(&request.content);
+    let (near_duplicates, template_reuse) = match &signature {
+        Some(signature) => {
+            let (found, reuse) = near_duplicates(pool, &rules, signature, request.author.as_deref(), None).await;
+            (Some(found), Some(reuse))
         }
+        None => (None, None),
     };
-    let heuristic_result = heuristic_handle
-        .await
-        .map_err(|e| AppError::Internal(format!("Heuristic analysis panicked: {e}")))?;
+    let context = Context {
+        baseline: author_baseline(pool, &rules, request.author.as_deref(), &platform).await,
+        template_reuse,
+    };
+
+    // Run every detector (heuristics, LLM) concurrently and combine them
+    let input = Input::new(request.content.clone(), request.platform.clone(), rules.clone(), context);
+    let (mut verdicts, failed) = ensemble.run(&input).await?;
+    let (final_score, confidence, detectors) = ensemble::combine(&verdicts, &failed, &rules);
 
-    let (final_score, confidence, llm_score_val) = if let Some(llm) = &llm_result {
-        // Weighted: 60% LLM, 40% heuristic
-        let combined = (llm.score as f64 * 0.6 + heuristic_result.score as f64 * 0.4).round() as u8;
-        let score = combined.min(10);
-        let conf = (llm.confidence * 0.7 + 0.3).min(1.0);
-        (score, conf, Some(llm.score))
+    let llm_score_val = verdicts
+        .iter()
+        .find(|(name, _)| ensemble::LLM_DETECTORS.contains(name))
+        .map(|(_, v)| v.score);
+    let transformer_score = verdicts.iter().find(|(name, _)| *name == "transformer").map(|(_, v)| v.score);
+    let watermark = verdicts
+        .iter_mut()
+        .find_map(|(_, v)| v.watermark.take().map(|w| (w, v.confidence)));
+    let neighbours = verdicts.iter_mut().find_map(|(_, v)| v.neighbours.take());
+    let mut signals = Vec::new();
+    let mut evidence: Vec<Evidence> = Vec::new();
+    for (_, verdict) in verdicts.iter_mut().filter(|(_, v)| v.heuristics.is_none()) {
+        signals.append(&mut verdict.signals);
+        evidence.append(&mut verdict.evidence);
+    }
+    let mut heuristic_result = verdicts
+        .into_iter()
+        .find_map(|(_, v)| v.heuristics)
+        .ok_or_else(|| AppError::Internal("Ensemble has no heuristic detector".to_string()))?;
+    heuristic_result.signals.extend(signals);
+    heuristic_result.evidence.extend(evidence);
+
+    // No lexicons for the detected language: only language-neutral heuristics ran
+    let confidence = if heuristic_result.language_supported {
+        confidence
+    } else {
+        confidence * rules.language.unsupported_confidence
+    };
+    // Too short for text statistics: scored on short-text features only
+    let confidence = if heuristic_result.short_text {
+        confidence * rules.short_text.confidence
     } else {
-        // Heuristics only — cap confidence at 0.5
-        let score = heuristic_result.score.min(10);
-        let conf = 0.5;
-        (score, conf, None)
+        confidence
     };
+    // A known watermark outranks every other detector
+    let (final_score, confidence) = match &watermark {
+        Some((w, watermark_confidence)) if w.detected => (10, confidence.max(*watermark_confidence)),
+        _ => (final_score, confidence),
+    };
+    let watermark = watermark.map(|(w, _)| w);
 
-    let heuristics_only = llm_score_val.is_none();
-    let label = score_to_label(final_score, heuristics_only);
+    let heuristics_only = !ensemble::second_opinion(&detectors);
+    let label = score_to_label(final_score, heuristics_only, rules.labels(Some(&request.platform)));
     let signals_json = serde_json::to_string(&heuristic_result.signals).unwrap_or_else(|_| "[]".to_string());
+    let evidence_json = serde_json::to_string(&heuristic_result.evidence).unwrap_or_else(|_| "[]".to_string());
+    let contributions_json =
+        serde_json::to_string(&heuristic_result.contributions).unwrap_or_else(|_| "[]".to_string());
+    let removed_json = serde_json::to_string(&heuristic_result.removed).unwrap_or_else(|_| "[]".to_string());
+    let detectors_json = serde_json::to_string(&detectors).unwrap_or_else(|_| "[]".to_string());
+    let watermark_json = watermark.as_ref().and_then(|w| serde_json::to_string(w).ok());
+    let style_json = serde_json::to_string(&heuristic_result.style).ok();
+    let neighbours_json = neighbours.as_ref().and_then(|n| serde_json::to_string(n).ok());
 
     // Store result
     let record = AnalysisRecord {
         id: uuid::Uuid::new_v4().to_string(),
         content_hash,
-        platform: request.platform.to_string(),
+        platform,
         post_id: request.post_id.clone(),
         author: request.author.clone(),
         score: final_score as i32,
@@ -148,6 +236,15 @@ pub async fn analyze(
         llm_score: llm_score_val.map(|s| s as i32),
         heuristic_score: heuristic_result.score as i32,
         signals: signals_json,
+        evidence: evidence_json,
+        contributions: contributions_json,
+        language: heuristic_result.language.clone(),
+        removed: removed_json,
+        detectors: detectors_json,
+        watermark: watermark_json,
+        style: style_json,
+        minhash: signature.as_deref().map(templates::to_bytes),
+        neighbours: neighbours_json,
         created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
     };
 
@@ -157,15 +254,90 @@ pub async fn analyze(
         score: final_score,
         confidence,
         label,
+        language: heuristic_result.language,
+        removed: heuristic_result.removed,
         breakdown: Breakdown {
             llm_score: llm_score_val,
             heuristic_score: heuristic_result.score,
+            transformer_score,
             signals: heuristic_result.signals,
+            evidence: heuristic_result.evidence,
+            contributions: heuristic_result.contributions,
+            detectors,
+            watermark,
+            near_duplicates,
+            neighbours,
+        },
+    })
+}
+
+/// The author's style baseline on `platform`; None for anonymous posts and authors
+/// with fewer than `author_baseline.min_posts` stored posts.
+async fn author_baseline(pool: &SqlitePool, rules: &Rules, author: Option<&str>, platform: &str) -> Option<Baseline> {
+    let author = author.filter(|a| !a.is_empty() && *a != "unknown")?;
+    let limit = rules.author_baseline.max_posts as i64;
+    let styles = match db::get_author_styles(pool, author, platform, limit).await {
+        Ok(styles) => styles,
+        Err(e) => {
+            tracing::warn!("Failed to load the style history of {author}: {e}");
+            return None;
+        }
+    };
+    let history: Vec<Style> = styles.iter().filter_map(|s| serde_json::from_str(s).ok()).collect();
+    (history.len() >= rules.author_baseline.min_posts).then(|| Baseline::new(&history))
+}
+
+/// Stored posts (any platform, except `exclude`) at least `templates.min_similarity`
+/// similar to `signature`, and how many accounts other than `author` posted them.
+async fn near_duplicates(
+    pool: &SqlitePool,
+    rules: &Rules,
+    signature: &[u32],
+    author: Option<&str>,
+    exclude: Option<&str>,
+) -> (NearDuplicates, usize) {
+    let mut candidates = db::find_fingerprints(pool, signature, templates::MAX_CANDIDATES)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to look up near-duplicates: {e}");
+            Vec::new()
+        });
+    candidates.retain(|c| Some(c.id.as_str()) != exclude);
+    templates::near_duplicates(signature, author, &candidates, rules.templates.min_similarity)
+}
+
+/// Heuristic-only explanation on the request's platform, against the author's
+/// baseline and stored near-duplicates: every vote, the floors reached, and the score with each of them
+/// removed. Nothing is cached or stored.
+pub async fn explain(pool: &SqlitePool, rules: Arc<Rules>, request: &AnalyzeRequest) -> Result<ExplainResponse, AppError> {
+    let text = request.content.clone();
+    let platform = request.platform.clone();
+    let rules_version = rules.version.clone();
+    let context = Context {
+        baseline: author_baseline(pool, &rules, request.author.as_deref(), &platform.to_string()).await,
+        template_reuse: match templates::signature(&text) {
+            Some(signature) => Some(near_duplicates(pool, &rules, &signature, request.author.as_deref(), None).await.1),
+            None => None,
         },
+    };
+    let result = tokio::task::spawn_blocking(move || heuristics::analyze_in_context(&text, &rules, Some(&platform), &context))
+        .await
+        .map_err(|e| AppError::Internal(format!("Heuristic analysis panicked: {e}")))?;
+
+    Ok(ExplainResponse {
+        heuristic_score: result.score,
+        raw_score: result.raw_score,
+        rules_version,
+        language: result.language.clone(),
+        counterfactuals: heuristics::counterfactuals(&result),
+        removed: result.removed,
+        signals: result.signals,
+        contributions: result.contributions,
+        floors: result.floors,
     })
 }
 
-fn hash_content(content: &str) -> String {
+pub fn hash_content(content: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
     hex::encode(hasher.finalize())
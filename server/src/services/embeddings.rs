@@ -0,0 +1,322 @@
+//! Nearest labeled neighbours in embedding space.
+//!
+//! Operators import reference corpora of known-AI and known-human posts
+//! (`aidetector-server import`). Each example is embedded through an
+//! OpenAI-compatible `/embeddings` endpoint (`EMBEDDINGS_URL`, which can be a
+//! local server) and stored with its label in `reference_examples`, next to
+//! the analyses. The detector embeds each post the same way, finds the
+//! `neighbours.k` most cosine-similar examples in a flat in-memory index, and
+//! scores the post by the similarity-weighted share of AI neighbours. The index
+//! holds the vectors of the configured model only, and is reloaded when an
+//! import changes the table.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::config::Endpoint;
+use crate::db;
+use crate::errors::AppError;
+use crate::models::{Neighbour, ReferenceRecord};
+use crate::services::ensemble::{Detector, Input, Verdict};
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+/// Embeddings of `texts` under `endpoint`'s model, in order.
+pub async fn embed(client: &Client, endpoint: &Endpoint, api_key: &str, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+    let url = format!("{}/embeddings", endpoint.url.trim_end_matches('/'));
+    let mut request = client.post(&url).json(&EmbeddingRequest {
+        model: &endpoint.model,
+        input: texts,
+    });
+    if !api_key.is_empty() {
+        request = request.header("Authorization", format!("Bearer {api_key}"));
+    }
+    let resp = request
+        .send()
+        .await
+        .map_err(|e| AppError::LlmApi(format!("Embeddings request to {url} failed: {e}")))?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(AppError::LlmApi(format!("Embeddings server returned {status}: {body}")));
+    }
+    let mut body: EmbeddingResponse = resp
+        .json()
+        .await
+        .map_err(|e| AppError::LlmApi(format!("Failed to parse embeddings response: {e}")))?;
+    if body.data.len() != texts.len() {
+        return Err(AppError::LlmApi(format!(
+            "{url} returned {} embeddings for {} inputs",
+            body.data.len(),
+            texts.len()
+        )));
+    }
+    body.data.sort_by_key(|d| d.index);
+    Ok(body.data.into_iter().map(|d| d.embedding).collect())
+}
+
+/// Little-endian bytes of an embedding, as stored.
+pub fn to_bytes(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+pub fn from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+/// `vector` scaled to unit length, None for a zero vector.
+fn normalized(mut vector: Vec<f32>) -> Option<Vec<f32>> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return None;
+    }
+    vector.iter_mut().for_each(|x| *x /= norm);
+    Some(vector)
+}
+
+struct Entry {
+    id: String,
+    corpus: String,
+    ai: bool,
+    content_preview: String,
+    vector: Vec<f32>,
+}
+
+/// Reference examples as unit vectors, searched exhaustively.
+#[derive(Default)]
+pub struct Index {
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    /// Examples whose embedding has the dimension most of them share; others
+    /// (a model swapped under the same name) are left out.
+    pub fn new(records: Vec<ReferenceRecord>) -> Self {
+        let mut dims: Vec<usize> = records.iter().map(|r| r.embedding.len() / 4).collect();
+        dims.sort_unstable();
+        let common = dims
+            .chunk_by(|a, b| a == b)
+            .max_by_key(|run| run.len())
+            .map_or(0, |run| run[0]);
+        let entries = records
+            .into_iter()
+            .filter_map(|r| {
+                let vector = normalized(from_bytes(&r.embedding)).filter(|v| v.len() == common)?;
+                Some(Entry {
+                    id: r.id,
+                    corpus: r.corpus,
+                    ai: r.label == "ai",
+                    content_preview: r.content_preview,
+                    vector,
+                })
+            })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The `k` examples most cosine-similar to `query` with a similarity of at
+    /// least `min_similarity`, closest first.
+    pub fn search(&self, query: Vec<f32>, k: usize, min_similarity: f64) -> Vec<Neighbour> {
+        let Some(query) = normalized(query) else {
+            return Vec::new();
+        };
+        let mut scored: Vec<(f64, &Entry)> = self
+            .entries
+            .iter()
+            .filter(|e| e.vector.len() == query.len())
+            .map(|e| (e.vector.iter().zip(&query).map(|(a, b)| a * b).sum::<f32>() as f64, e))
+            .filter(|(similarity, _)| *similarity >= min_similarity)
+            .collect();
+        if scored.len() > k {
+            scored.select_nth_unstable_by(k, |a, b| b.0.total_cmp(&a.0));
+            scored.truncate(k);
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored
+            .into_iter()
+            .map(|(similarity, e)| Neighbour {
+                id: e.id.clone(),
+                corpus: e.corpus.clone(),
+                label: if e.ai { "ai" } else { "human" }.to_string(),
+                similarity: (similarity * 1000.0).round() / 1000.0,
+                content_preview: e.content_preview.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Score and confidence from `neighbours`: the AI share weighted by similarity,
+/// with confidence the agreement scaled by how close they are. None without neighbours.
+pub fn knn(neighbours: &[Neighbour]) -> Option<(u8, f64)> {
+    let weights: Vec<f64> = neighbours.iter().map(|n| n.similarity.max(0.0)).collect();
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let ai: f64 = neighbours.iter().zip(&weights).filter(|(n, _)| n.label == "ai").map(|(_, w)| w).sum();
+    let p = ai / total;
+    let closeness = total / neighbours.len() as f64;
+    Some(((p * 10.0).round() as u8, (p.max(1.0 - p) * closeness).clamp(0.0, 1.0)))
+}
+
+/// Reference corpus lookups as an ensemble detector.
+pub struct Embeddings {
+    client: Client,
+    endpoint: Endpoint,
+    api_key: String,
+    pool: SqlitePool,
+    index: RwLock<Loaded>,
+}
+
+/// The index and the reference table version it was loaded at.
+#[derive(Default)]
+struct Loaded {
+    version: Option<(i64, i64)>,
+    index: Arc<Index>,
+}
+
+impl Embeddings {
+    pub fn new(client: &Client, endpoint: Endpoint, api_key: String, pool: SqlitePool) -> Self {
+        Self {
+            client: client.clone(),
+            endpoint,
+            api_key,
+            pool,
+            index: RwLock::new(Loaded::default()),
+        }
+    }
+
+    /// The index, reloaded first if the reference table changed.
+    async fn index(&self) -> Result<Arc<Index>, AppError> {
+        let version = db::references_version(&self.pool, &self.endpoint.model).await?;
+        {
+            let loaded = self.index.read().await;
+            if loaded.version == Some(version) {
+                return Ok(loaded.index.clone());
+            }
+        }
+        let mut loaded = self.index.write().await;
+        if loaded.version != Some(version) {
+            let records = db::get_references(&self.pool, &self.endpoint.model).await?;
+            let index = tokio::task::spawn_blocking(move || Index::new(records))
+                .await
+                .map_err(|e| AppError::Internal(format!("Loading the reference index panicked: {e}")))?;
+            tracing::info!("Reference index loaded: {} examples for {}", index.len(), self.endpoint.model);
+            *loaded = Loaded {
+                version: Some(version),
+                index: Arc::new(index),
+            };
+        }
+        Ok(loaded.index.clone())
+    }
+}
+
+#[async_trait]
+impl Detector for Embeddings {
+    fn name(&self) -> &'static str {
+        "embeddings"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let index = self.index().await?;
+        let text = input.stripped();
+        let neighbours = if index.is_empty() || text.trim().is_empty() {
+            Vec::new()
+        } else {
+            let vector = embed(&self.client, &self.endpoint, &self.api_key, &[text])
+                .await?
+                .pop()
+                .unwrap_or_default();
+            let (k, min_similarity) = (input.rules.neighbours.k, input.rules.neighbours.min_similarity);
+            tokio::task::spawn_blocking(move || index.search(vector, k, min_similarity))
+                .await
+                .map_err(|e| AppError::Internal(format!("Reference search panicked: {e}")))?
+        };
+        // No close examples is no evidence either way
+        let (score, confidence) = knn(&neighbours).unwrap_or((input.rules.prior.score.round() as u8, 0.0));
+        Ok(Verdict {
+            score,
+            confidence,
+            neighbours: Some(neighbours),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, label: &str, vector: &[f32]) -> ReferenceRecord {
+        ReferenceRecord {
+            id: id.to_string(),
+            corpus: "test".to_string(),
+            label: label.to_string(),
+            content_preview: String::new(),
+            embedding: to_bytes(vector),
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let index = Index::new(vec![
+            record("ai1", "ai", &[1.0, 0.0, 0.0]),
+            record("ai2", "ai", &[0.9, 0.1, 0.0]),
+            record("human", "human", &[0.0, 1.0, 0.0]),
+            record("far", "human", &[-1.0, 0.0, 0.0]),
+            // Other dimension: left out
+            record("odd", "ai", &[1.0, 0.0]),
+        ]);
+        assert_eq!(index.len(), 4);
+        let found = index.search(vec![2.0, 0.2, 0.0], 3, 0.0);
+        assert_eq!(found.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), ["ai2", "ai1", "human"]);
+        assert!(found[0].similarity > 0.99);
+        // Below the similarity floor
+        assert_eq!(index.search(vec![2.0, 0.2, 0.0], 3, 0.5).len(), 2);
+        assert!(index.search(vec![0.0, 0.0, 0.0], 3, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_knn() {
+        let neighbour = |label: &str, similarity: f64| Neighbour {
+            id: String::new(),
+            corpus: String::new(),
+            label: label.to_string(),
+            similarity,
+            content_preview: String::new(),
+        };
+        let (score, confidence) = knn(&[neighbour("ai", 0.9), neighbour("ai", 0.9), neighbour("human", 0.6)]).unwrap();
+        assert_eq!(score, 8); // 1.8 / 2.4
+        assert!((confidence - 0.75 * 0.8).abs() < 1e-9);
+        assert_eq!(knn(&[neighbour("human", 0.8)]).unwrap().0, 0);
+        assert!(knn(&[]).is_none());
+    }
+}
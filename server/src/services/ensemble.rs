@@ -0,0 +1,480 @@
+//! Detector ensemble: every score source behind one trait.
+//!
+//! Heuristics and each LLM provider implement `Detector`. An `Ensemble` runs
+//! its detectors concurrently on a post and `combine` merges their verdicts
+//! with the strategy and weights from the rules file, so a new score source
+//! is one more `Detector` instead of another branch in `detector::analyze`.
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use reqwest::Client;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::config::{Config, LlmProvider};
+use crate::errors::AppError;
+use crate::models::{DetectorResult, Evidence, Neighbour, Platform, WatermarkResult};
+use crate::services::classifier::{Classifier, Model};
+use crate::services::embeddings::Embeddings;
+use crate::services::heuristics::{Analysis, Context, HeuristicResult, Heuristics};
+use crate::services::logprobs::LogProbs;
+use crate::services::perplexity::{LanguageModel, Perplexity};
+use crate::services::rules::{Confidence, Rules, Strategy, Tier};
+use crate::services::{anthropic, detector, entities, openrouter};
+
+/// Detector names the rules file may weight.
+pub const DETECTORS: &[&str] = &["heuristics", "classifier", "perplexity", "transformer", "logprobs", "embeddings", "watermark", "anthropic", "openrouter"];
+
+/// Detectors that are language models, reported as `breakdown.llm_score`.
+pub const LLM_DETECTORS: &[&str] = &["anthropic", "openrouter"];
+
+/// Detectors `LLM_PROVIDER` can select. With the heuristics, their failure fails
+/// the analysis; other detectors that fail are left out.
+pub const PROVIDERS: &[&str] = &["anthropic", "openrouter", "transformer"];
+
+/// Confidence of a detector whose score is not a probability (the heuristics,
+/// perplexity, log probabilities). Deliberately middling: agreement with a
+/// second opinion is what raises the final confidence.
+pub const CONFIDENCE: f64 = 0.5;
+
+/// A post as every detector sees it.
+pub struct Input {
+    pub content: String,
+    pub platform: Platform,
+    pub rules: Arc<Rules>,
+    /// What stored analyses say about the post, for the heuristics.
+    pub context: Arc<Context>,
+    /// The heuristic analysis, run once by whichever detector needs it first.
+    analysis: OnceCell<Arc<Analysis>>,
+}
+
+impl Input {
+    pub fn new(content: String, platform: Platform, rules: Arc<Rules>, context: Context) -> Self {
+        Self {
+            content,
+            platform,
+            rules,
+            context: Arc::new(context),
+            analysis: OnceCell::new(),
+        }
+    }
+
+    /// The heuristic analysis of the post in its context, shared by the
+    /// heuristics and the detectors that read its measurements or sentences.
+    pub async fn analysis(&self) -> Result<Arc<Analysis>, AppError> {
+        self.analysis
+            .get_or_try_init(|| async {
+                let text = self.content.clone();
+                let platform = self.platform.clone();
+                let rules = self.rules.clone();
+                let context = self.context.clone();
+                tokio::task::spawn_blocking(move || Arc::new(Analysis::new(&text, &rules, Some(&platform), &context)))
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Heuristic analysis panicked: {e}")))
+            })
+            .await
+            .cloned()
+    }
+
+    /// LLM system prompt with the platform's additions.
+    pub fn system_prompt(&self) -> String {
+        detector::system_prompt(self.rules.prompt(Some(&self.platform)))
+    }
+
+    /// The content without links, mentions, hashtags or quotes, for language models.
+    pub fn stripped(&self) -> String {
+        entities::strip(&self.content)
+    }
+}
+
+/// One detector's opinion of a post.
+#[derive(Debug, Default)]
+pub struct Verdict {
+    /// 0-10, higher is more likely AI.
+    pub score: u8,
+    /// 0-1.
+    pub confidence: f64,
+    /// Signals that fired, reported alongside the heuristic ones.
+    pub signals: Vec<String>,
+    pub evidence: Vec<Evidence>,
+    /// The full heuristic result; set by the heuristic detector only.
+    pub heuristics: Option<HeuristicResult>,
+    /// Watermark test of the best-matching scheme; set by the watermark detector only.
+    pub watermark: Option<WatermarkResult>,
+    /// Closest labeled reference examples; set by the embeddings detector only.
+    pub neighbours: Option<Vec<Neighbour>>,
+}
+
+impl Verdict {
+    /// The verdict of a model giving the probability `p` that a post is AI. Its
+    /// confidence is the probability of the predicted class.
+    pub fn probability(p: f64) -> Self {
+        Self {
+            score: (p * 10.0).round() as u8,
+            confidence: p.max(1.0 - p),
+            ..Default::default()
+        }
+    }
+}
+
+#[async_trait]
+pub trait Detector: Send + Sync {
+    /// Name in the breakdown and in `ensemble.weights`.
+    fn name(&self) -> &'static str;
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError>;
+}
+
+/// The local transformer detector. Panics on an unloadable model.
+#[cfg(feature = "transformer")]
+fn transformer(config: &Config) -> Box<dyn Detector> {
+    let dir = config.transformer_model_path.as_ref().expect("Transformer provider needs a model path");
+    let model = crate::services::transformer::Transformer::load(dir)
+        .unwrap_or_else(|e| panic!("Invalid transformer model: {e}"));
+    tracing::info!("Transformer model loaded from {}", dir.display());
+    Box::new(model)
+}
+
+#[cfg(not(feature = "transformer"))]
+fn transformer(_: &Config) -> Box<dyn Detector> {
+    panic!("TRANSFORMER_MODEL_PATH needs a server built with `--features transformer`");
+}
+
+/// The watermark detector. Panics on an unloadable schemes file.
+#[cfg(feature = "watermark")]
+fn watermark(path: &std::path::Path) -> Box<dyn Detector> {
+    let watermark = crate::services::watermark::Watermark::load(path)
+        .unwrap_or_else(|e| panic!("Invalid watermark schemes: {e}"));
+    tracing::info!("Watermark schemes loaded: {}", watermark.scheme_names().join(", "));
+    Box::new(watermark)
+}
+
+#[cfg(not(feature = "watermark"))]
+fn watermark(_: &std::path::Path) -> Box<dyn Detector> {
+    panic!("WATERMARK_PATH needs a server built with `--features watermark`");
+}
+
+/// The detectors run on every post.
+pub struct Ensemble {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl Ensemble {
+    pub fn new(detectors: Vec<Box<dyn Detector>>) -> Self {
+        Self { detectors }
+    }
+
+    /// Heuristics, plus the trained classifier, the n-gram language model, the
+    /// watermark schemes, the logprobs servers, the embeddings server and the
+    /// configured LLM provider if set. Panics on an unreadable model, like invalid
+    /// rules. The embeddings detector reads its reference examples from `pool`.
+    pub fn from_config(client: &Client, config: &Config, pool: &SqlitePool) -> Self {
+        let mut detectors: Vec<Box<dyn Detector>> = vec![Box::new(Heuristics)];
+        if let Some(path) = &config.classifier_path {
+            let model = Model::load(path).unwrap_or_else(|e| panic!("Invalid classifier model: {e}"));
+            tracing::info!("Classifier model loaded from {}", path.display());
+            detectors.push(Box::new(Classifier::new(model)));
+        }
+        if let Some(path) = &config.perplexity_model_path {
+            let model = LanguageModel::load(path).unwrap_or_else(|e| panic!("Invalid language model: {e}"));
+            tracing::info!("{}-gram language model loaded from {}", model.order(), path.display());
+            detectors.push(Box::new(Perplexity::new(model)));
+        }
+        if let Some(path) = &config.watermark_path {
+            detectors.push(watermark(path));
+        }
+        if let Some(observer) = &config.logprobs {
+            let performer = config.logprobs_performer.clone();
+            match &performer {
+                Some(p) => tracing::info!("Binoculars scoring: {} observes, {} performs", observer.model, p.model),
+                None => tracing::info!("Token log probabilities from {} ({})", observer.url, observer.model),
+            }
+            detectors.push(Box::new(LogProbs::new(client, observer.clone(), performer)));
+        }
+        if let Some(endpoint) = &config.embeddings {
+            tracing::info!("Reference corpus lookups embedded by {} ({})", endpoint.url, endpoint.model);
+            let api_key = config.embeddings_api_key.clone();
+            detectors.push(Box::new(Embeddings::new(client, endpoint.clone(), api_key, pool.clone())));
+        }
+        match config.llm_provider {
+            LlmProvider::Anthropic => detectors.push(Box::new(anthropic::Anthropic::new(client, config))),
+            LlmProvider::OpenRouter => detectors.push(Box::new(openrouter::OpenRouter::new(client, config))),
+            LlmProvider::Transformer => detectors.push(transformer(config)),
+            LlmProvider::None => tracing::debug!("No LLM provider configured — using heuristics only"),
+        }
+        Self::new(detectors)
+    }
+
+    /// Run every detector concurrently. Returns the verdicts and the optional
+    /// detectors that failed, with their errors; fails if the heuristics or the
+    /// LLM provider fail.
+    pub async fn run(&self, input: &Input) -> Result<(Vec<(&'static str, Verdict)>, Vec<(&'static str, String)>), AppError> {
+        let mut verdicts = Vec::new();
+        let mut failed = Vec::new();
+        for (name, result) in join_all(self.detectors.iter().map(|d| async move { (d.name(), d.detect(input).await) })).await {
+            match result {
+                Ok(verdict) => verdicts.push((name, verdict)),
+                Err(e) if name == "heuristics" || PROVIDERS.contains(&name) => return Err(e),
+                Err(e) => {
+                    tracing::warn!("{name} detector failed, left out: {e:?}");
+                    failed.push((name, e.to_string()));
+                }
+            }
+        }
+        Ok((verdicts, failed))
+    }
+}
+
+/// Prior-weighted vote of measurements taken outside the heuristic engine, by the
+/// rules' tiers for them on `platform`. Returns the score and each tier that voted.
+pub fn vote<'n, 'r>(
+    rules: &'r Rules,
+    platform: Option<&Platform>,
+    measured: &[(&'n str, f64)],
+) -> (u8, Vec<(&'n str, &'r Tier)>) {
+    let mut votes = vec![(rules.prior.score, rules.prior.weight)];
+    let mut fired = Vec::new();
+    for &(name, value) in measured {
+        if let Some(tier) = rules.tiers_for(platform, name).iter().find(|t| t.matches(value)) {
+            votes.push((tier.score, tier.weight * rules.weight_scale(platform, name)));
+            fired.push((name, tier));
+        }
+    }
+    let weight_sum: f64 = votes.iter().map(|(_, w)| w).sum();
+    let score = if weight_sum > 0.0 {
+        votes.iter().map(|(s, w)| s * w).sum::<f64>() / weight_sum
+    } else {
+        0.0
+    };
+    ((score.round() as u8).min(10), fired)
+}
+
+/// Final score and confidence from the verdicts, plus each detector's result,
+/// failed detectors last. Confidence follows `ensemble.confidence`.
+pub fn combine(
+    verdicts: &[(&'static str, Verdict)],
+    failed: &[(&'static str, String)],
+    rules: &Rules,
+) -> (u8, f64, Vec<DetectorResult>) {
+    let mut results: Vec<DetectorResult> = verdicts
+        .iter()
+        .map(|(name, v)| DetectorResult {
+            name: name.to_string(),
+            score: v.score,
+            confidence: v.confidence,
+            weight: rules.detector_weight(name),
+            error: None,
+        })
+        .collect();
+    let failures = failed.iter().map(|(name, error)| DetectorResult {
+        name: name.to_string(),
+        score: 0,
+        confidence: 0.0,
+        weight: rules.detector_weight(name),
+        error: Some(error.clone()),
+    });
+    if results.is_empty() {
+        results.extend(failures);
+        return (0, 0.0, results);
+    }
+
+    // All-zero weights fall back to a plain mean
+    let weight_sum: f64 = results.iter().map(|r| r.weight).sum();
+    let weight = |r: &DetectorResult| if weight_sum > 0.0 { r.weight } else { 1.0 };
+    let total: f64 = results.iter().map(weight).sum();
+    let mean = |value: &dyn Fn(&DetectorResult) -> f64| results.iter().map(|r| value(r) * weight(r)).sum::<f64>() / total;
+    let mut confidence = mean(&|r| r.confidence);
+
+    let score = match rules.ensemble.strategy {
+        Strategy::WeightedMean => mean(&|r| r.score as f64),
+        Strategy::Max => {
+            // Detectors weighted 0 are reported but don't vote
+            let top = results
+                .iter()
+                .filter(|r| weight(r) > 0.0)
+                .max_by_key(|r| r.score)
+                .expect("some detector has weight");
+            confidence = top.confidence;
+            top.score as f64
+        }
+        Strategy::Stacking => {
+            let z = rules.ensemble.intercept + results.iter().map(|r| r.weight * r.score as f64).sum::<f64>();
+            10.0 / (1.0 + (-z).exp())
+        }
+    };
+    if rules.ensemble.confidence == Confidence::Provider {
+        let of = |names: &[&str]| results.iter().find(|r| names.contains(&r.name.as_str())).map(|r| r.confidence);
+        confidence = match of(PROVIDERS) {
+            Some(provider) => 0.3 + 0.7 * provider,
+            None => of(&["heuristics"]).unwrap_or(confidence),
+        };
+    }
+    results.extend(failures);
+    ((score.round() as u8).min(10), confidence.clamp(0.0, 1.0), results)
+}
+
+/// Whether a detector besides the heuristics, weighted above 0, returned a
+/// verdict; without one the middle of the scale is labeled "uncertain".
+pub fn second_opinion(results: &[DetectorResult]) -> bool {
+    results
+        .iter()
+        .any(|r| r.name != "heuristics" && r.error.is_none() && r.weight > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::rules::EnsembleRules;
+
+    struct Fixed(&'static str, u8, f64);
+
+    #[async_trait]
+    impl Detector for Fixed {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn detect(&self, _: &Input) -> Result<Verdict, AppError> {
+            Ok(Verdict {
+                score: self.1,
+                confidence: self.2,
+                ..Default::default()
+            })
+        }
+    }
+
+    struct Down(&'static str);
+
+    #[async_trait]
+    impl Detector for Down {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn detect(&self, _: &Input) -> Result<Verdict, AppError> {
+            Err(AppError::LlmApi("connection refused".to_string()))
+        }
+    }
+
+    fn rules(strategy: Strategy, confidence: Confidence) -> Rules {
+        let mut rules = Rules::builtin();
+        rules.ensemble = EnsembleRules {
+            strategy,
+            weights: [("heuristics".to_string(), 0.4), ("anthropic".to_string(), 0.6)].into(),
+            intercept: -5.0,
+            confidence,
+        };
+        rules
+    }
+
+    async fn run(detectors: Vec<Box<dyn Detector>>, rules: &Rules) -> (u8, f64, Vec<DetectorResult>) {
+        let input = Input::new(
+            "Great insights! Thanks for sharing.".to_string(),
+            Platform::Twitter,
+            Arc::new(Rules::builtin()),
+            Context::default(),
+        );
+        let (verdicts, failed) = Ensemble::new(detectors).run(&input).await.unwrap();
+        combine(&verdicts, &failed, rules)
+    }
+
+    #[tokio::test]
+    async fn test_strategies() {
+        let detectors = || -> Vec<Box<dyn Detector>> {
+            vec![Box::new(Fixed("heuristics", 2, 0.5)), Box::new(Fixed("anthropic", 9, 0.9))]
+        };
+
+        let (score, confidence, results) = run(detectors(), &rules(Strategy::WeightedMean, Confidence::Detectors)).await;
+        assert_eq!(score, 6); // 2 * 0.4 + 9 * 0.6 = 6.2
+        assert!((confidence - 0.74).abs() < 1e-9);
+        assert_eq!(results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["heuristics", "anthropic"]);
+        assert_eq!(results[1].weight, 0.6);
+
+        let (score, confidence, _) = run(detectors(), &rules(Strategy::Max, Confidence::Detectors)).await;
+        assert_eq!((score, confidence), (9, 0.9));
+
+        let (score, _, _) = run(detectors(), &rules(Strategy::Stacking, Confidence::Detectors)).await;
+        assert_eq!(score, 8); // 10 * sigmoid(-5 + 0.8 + 5.4) = 7.7
+    }
+
+    #[tokio::test]
+    async fn test_provider_confidence() {
+        let rules = rules(Strategy::WeightedMean, Confidence::Provider);
+        // 0.3 + 0.7 * the LLM's confidence, whatever else ran
+        let detectors: Vec<Box<dyn Detector>> = vec![
+            Box::new(Fixed("heuristics", 2, 0.5)),
+            Box::new(Fixed("classifier", 7, 0.2)),
+            Box::new(Fixed("anthropic", 9, 0.9)),
+        ];
+        let (_, confidence, _) = run(detectors, &rules).await;
+        assert!((confidence - 0.93).abs() < 1e-9);
+        // The heuristics' own confidence without a provider
+        let detectors: Vec<Box<dyn Detector>> =
+            vec![Box::new(Fixed("heuristics", 2, 0.5)), Box::new(Fixed("classifier", 7, 0.9))];
+        let (_, confidence, _) = run(detectors, &rules).await;
+        assert_eq!(confidence, 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_second_opinion() {
+        let mut rules = rules(Strategy::WeightedMean, Confidence::Provider);
+        rules.ensemble.weights.insert("perplexity".to_string(), 0.0);
+        // A trained classifier is a second opinion without any LLM
+        let detectors: Vec<Box<dyn Detector>> =
+            vec![Box::new(Fixed("heuristics", 5, 0.5)), Box::new(Fixed("classifier", 5, 0.8))];
+        assert!(second_opinion(&run(detectors, &rules).await.2));
+        // Failed and unweighted detectors are not
+        let detectors: Vec<Box<dyn Detector>> =
+            vec![Box::new(Fixed("heuristics", 5, 0.5)), Box::new(Down("embeddings")), Box::new(Fixed("perplexity", 5, 0.5))];
+        assert!(!second_opinion(&run(detectors, &rules).await.2));
+    }
+
+    #[tokio::test]
+    async fn test_failed_detectors() {
+        let rules = rules(Strategy::WeightedMean, Confidence::Provider);
+        // An optional detector that fails is reported and left out of the score
+        let detectors: Vec<Box<dyn Detector>> =
+            vec![Box::new(Fixed("heuristics", 2, 0.5)), Box::new(Down("embeddings")), Box::new(Fixed("anthropic", 9, 0.9))];
+        let (score, _, results) = run(detectors, &rules).await;
+        assert_eq!(score, 6);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2].name, "embeddings");
+        assert_eq!(results[2].error.as_deref(), Some("LLM API error: connection refused"));
+
+        // The LLM provider failing fails the analysis
+        let input = Input::new(
+            "Great insights! Thanks for sharing.".to_string(),
+            Platform::Twitter,
+            Arc::new(Rules::builtin()),
+            Context::default(),
+        );
+        let ensemble = Ensemble::new(vec![Box::new(Fixed("heuristics", 2, 0.5)), Box::new(Down("anthropic"))]);
+        assert!(ensemble.run(&input).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_heuristic_detector_in_ensemble() {
+        let input = Input::new(
+            "In today's world, it's important to note that we leverage synergy.".to_string(),
+            Platform::LinkedIn,
+            Arc::new(Rules::builtin()),
+            Context {
+                baseline: None,
+                template_reuse: Some(3),
+            },
+        );
+        let (verdicts, _) = Ensemble::new(vec![Box::new(Heuristics)]).run(&input).await.unwrap();
+        let (name, verdict) = &verdicts[0];
+        assert_eq!(*name, "heuristics");
+        let result = verdict.heuristics.as_ref().unwrap();
+        assert_eq!(verdict.score, result.score);
+        assert_eq!(verdict.evidence.len(), result.evidence.len());
+        assert!(result.measurements.contains(&("template_reuse", 3.0)));
+
+        // Later detectors read the same analysis instead of running their own
+        let analysis = input.analysis().await.unwrap();
+        assert!(Arc::ptr_eq(&analysis, &input.analysis().await.unwrap()));
+        assert_eq!(analysis.result.measurements, result.measurements);
+    }
+}
@@ -0,0 +1,260 @@
+//! Post entities: links, @mentions, $cashtags, #hashtags and quoted text.
+//!
+//! None of these are the author's prose. A link counts as one long "word", a
+//! block of ten hashtags reads as a ten-word sentence, and a quoted tweet
+//! brings someone else's style along. They are found here, counted as signals
+//! of their own, and cut from the text every other signal reads. Inline
+//! hashtags ("loving #rustlang today") are part of the sentence, so only their
+//! `#` goes; hashtags on hashtag-only lines go entirely.
+
+use std::ops::Range;
+
+use crate::services::text;
+
+/// Quotation marks that can wrap a quoted line.
+const QUOTES: &[(char, char)] = &[('"', '"'), ('“', '”'), ('«', '»'), ('„', '“')];
+
+/// Punctuation that ends a sentence around a link rather than belonging to it.
+const URL_TRAILERS: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '}', '"', '\'', '”', '’', '…'];
+
+#[derive(Debug, Default)]
+pub struct Entities {
+    pub urls: Vec<Range<usize>>,
+    /// `@handle`, including the `.` of the `.@handle` idiom.
+    pub mentions: Vec<Range<usize>>,
+    /// `$TICKER` symbols.
+    pub cashtags: Vec<Range<usize>>,
+    /// Hashtags on lines holding nothing but hashtags.
+    pub hashtags: Vec<Range<usize>>,
+    /// Hashtags inside a sentence.
+    pub inline_hashtags: Vec<Range<usize>>,
+    /// Hashtags in the run of hashtag-only lines that ends the post.
+    pub trailing_hashtags: usize,
+    /// Block-quoted lines (`> ...`) and lines that are one quotation.
+    pub quoted: Vec<Range<usize>>,
+}
+
+impl Entities {
+    pub fn hashtag_count(&self) -> usize {
+        self.hashtags.len() + self.inline_hashtags.len()
+    }
+
+    /// Every entity as (kind, span), sorted by position.
+    pub fn all(&self) -> Vec<(&'static str, Range<usize>)> {
+        let mut out: Vec<(&'static str, Range<usize>)> = [
+            ("url", &self.urls),
+            ("mention", &self.mentions),
+            ("cashtag", &self.cashtags),
+            ("hashtag", &self.hashtags),
+            ("hashtag", &self.inline_hashtags),
+            ("quote", &self.quoted),
+        ]
+        .into_iter()
+        .flat_map(|(kind, spans)| spans.iter().map(move |s| (kind, s.clone())))
+        .collect();
+        out.sort_by_key(|(_, span)| (span.start, span.end));
+        out
+    }
+
+    /// Byte ranges to cut from the text, sorted and non-overlapping. Inline
+    /// hashtags only lose their `#`.
+    pub fn cuts(&self) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = self
+            .urls
+            .iter()
+            .chain(&self.mentions)
+            .chain(&self.cashtags)
+            .chain(&self.hashtags)
+            .chain(&self.quoted)
+            .cloned()
+            .chain(self.inline_hashtags.iter().map(|h| h.start..h.start + 1))
+            .collect();
+        spans.sort_by_key(|s| s.start);
+
+        let mut merged: Vec<Range<usize>> = Vec::new();
+        for span in spans {
+            match merged.last_mut() {
+                Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
+}
+
+pub fn find(text: &str) -> Entities {
+    let urls = find_urls(text);
+    let outside_urls = |span: &Range<usize>| !urls.iter().any(|u| u.start <= span.start && span.end <= u.end);
+
+    let mentions: Vec<Range<usize>> = find_prefixed(text, '@', |c| c.is_whitespace() || "(.".contains(c))
+        .into_iter()
+        .filter(outside_urls)
+        .map(|m| {
+            // ".@handle" opening a post or line is a reply idiom, not a sentence end
+            let before = &text[..m.start];
+            let idiom = before.strip_suffix('.').is_some_and(|b| b.chars().next_back().is_none_or(char::is_whitespace));
+            if idiom { m.start - 1..m.end } else { m }
+        })
+        .collect();
+    let cashtags = find_prefixed(text, '$', |c| c.is_whitespace() || c == '(')
+        .into_iter()
+        .filter(outside_urls)
+        .filter(|c| {
+            let symbol = &text[c.start + 1..c.end];
+            symbol.len() <= 6 && symbol.chars().all(|ch| ch.is_ascii_alphabetic())
+        })
+        .collect();
+
+    let lines = text::line_spans(text);
+    let all_hashtags: Vec<Range<usize>> = find_prefixed(text, '#', char::is_whitespace)
+        .into_iter()
+        .filter(outside_urls)
+        .collect();
+    let block_lines: Vec<bool> = lines.iter().map(|l| hashtags_only(text, l, &all_hashtags).is_some()).collect();
+    let (hashtags, inline_hashtags) = all_hashtags.into_iter().partition::<Vec<_>, _>(|h| {
+        lines
+            .iter()
+            .zip(&block_lines)
+            .any(|(line, block)| *block && line.start <= h.start && h.end <= line.end)
+    });
+    let trailing_hashtags = lines
+        .iter()
+        .rev()
+        .map_while(|line| hashtags_only(text, line, &hashtags))
+        .sum();
+
+    Entities {
+        urls,
+        mentions,
+        cashtags,
+        hashtags,
+        inline_hashtags,
+        trailing_hashtags,
+        quoted: lines.into_iter().filter(|l| is_quoted(&text[l.clone()])).collect(),
+    }
+}
+
+/// `text` with every entity cut out, for the LLM.
+pub fn strip(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut at = 0;
+    for cut in find(text).cuts() {
+        out.push_str(&text[at..cut.start]);
+        at = cut.end;
+    }
+    out.push_str(&text[at..]);
+    out
+}
+
+/// `http(s)://` and `www.` links, without trailing sentence punctuation.
+fn find_urls(text: &str) -> Vec<Range<usize>> {
+    let mut out: Vec<Range<usize>> = Vec::new();
+    for (i, _) in text.char_indices() {
+        if out.last().is_some_and(|u| i < u.end) {
+            continue;
+        }
+        let rest = &text[i..];
+        let starts_link = ["https://", "http://", "www."]
+            .iter()
+            .any(|p| rest.len() >= p.len() && rest.is_char_boundary(p.len()) && rest[..p.len()].eq_ignore_ascii_case(p));
+        let at_word_start = text[..i].chars().next_back().is_none_or(|c| !c.is_alphanumeric());
+        if !starts_link || !at_word_start {
+            continue;
+        }
+        let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let link = rest[..len].trim_end_matches(URL_TRAILERS);
+        out.push(i..i + link.len());
+    }
+    out
+}
+
+/// `<prefix>name` tokens where the prefix follows the start of the text or a
+/// character accepted by `after`, and `name` is letters, digits or `_` with at
+/// least one letter ("#42" is an issue number, not a hashtag).
+fn find_prefixed(text: &str, prefix: char, after: impl Fn(char) -> bool) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    for (i, _) in text.match_indices(prefix) {
+        if text[..i].chars().next_back().is_some_and(|c| !after(c)) {
+            continue;
+        }
+        let name_start = i + prefix.len_utf8();
+        let name_len = text[name_start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(text.len() - name_start);
+        if text[name_start..name_start + name_len].chars().any(char::is_alphabetic) {
+            out.push(i..name_start + name_len);
+        }
+    }
+    out
+}
+
+/// Number of hashtags in `span` when it holds hashtags and nothing else.
+fn hashtags_only(text: &str, span: &Range<usize>, hashtags: &[Range<usize>]) -> Option<usize> {
+    let tags: Vec<&Range<usize>> = hashtags
+        .iter()
+        .filter(|h| h.start >= span.start && h.end <= span.end)
+        .collect();
+    let mut rest = text[span.clone()].to_string();
+    for tag in tags.iter().rev() {
+        rest.replace_range(tag.start - span.start..tag.end - span.start, "");
+    }
+    (!tags.is_empty() && !rest.chars().any(char::is_alphanumeric)).then_some(tags.len())
+}
+
+/// A `>` block quote, or a line of three or more words wrapped in quotation marks.
+fn is_quoted(line: &str) -> bool {
+    if line.starts_with('>') {
+        return true;
+    }
+    QUOTES.iter().any(|(open, close)| {
+        line.strip_prefix(*open)
+            .and_then(|rest| rest.trim_end_matches(['.', ',', '!', '?']).strip_suffix(*close))
+            .is_some_and(|inner| !inner.contains([*open, *close]) && inner.split_whitespace().count() >= 3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched<'a>(text: &'a str, spans: &[Range<usize>]) -> Vec<&'a str> {
+        spans.iter().map(|s| &text[s.clone()]).collect()
+    }
+
+    #[test]
+    fn test_links_mentions_and_cashtags() {
+        let text = ".@tensol_ai ships today (see https://tensol.ai/launch?ref=x#top). \
+                    Ping me at bob@example.com or @bob_dev. $NVDA up, paid $15k, www.example.com.";
+        let e = find(text);
+        assert_eq!(matched(text, &e.urls), ["https://tensol.ai/launch?ref=x#top", "www.example.com"]);
+        assert_eq!(matched(text, &e.mentions), [".@tensol_ai", "@bob_dev"]);
+        assert_eq!(matched(text, &e.cashtags), ["$NVDA"]);
+        assert_eq!(e.hashtag_count(), 0);
+    }
+
+    #[test]
+    fn test_hashtag_count_and_placement() {
+        let text = "Loved the #rustlang meetup tonight.\n\n#AI #Leadership #Growth\n#Innovation";
+        let e = find(text);
+        assert_eq!(matched(text, &e.inline_hashtags), ["#rustlang"]);
+        assert_eq!(e.hashtag_count(), 5);
+        assert_eq!(e.trailing_hashtags, 4);
+        assert_eq!(find("issue #42 fixed, thanks #rustlang folks").hashtag_count(), 1);
+    }
+
+    #[test]
+    fn test_quoted_lines() {
+        let text = "Saw this today:\n> Ship fast and break nothing\n“Discipline beats motivation every time.”\nSo true.";
+        let e = find(text);
+        assert_eq!(
+            matched(text, &e.quoted),
+            ["> Ship fast and break nothing", "“Discipline beats motivation every time.”"]
+        );
+    }
+
+    #[test]
+    fn test_strip() {
+        let text = "Loving #rustlang with @alice, details at https://example.com.\n\n#dev #code";
+        assert_eq!(strip(text), "Loving rustlang with , details at .\n\n ");
+    }
+}
@@ -1,383 +1,632 @@
+use async_trait::async_trait;
 use std::collections::HashSet;
-
-#[derive(Debug)]
+use std::ops::Range;
+
+use crate::errors::AppError;
+use crate::models::{Contribution, Counterfactual, Evidence, FloorHit, Platform, Removed};
+use crate::services::baseline::{self, Baseline, Style};
+use crate::services::ensemble::{self, Detector, Input, Verdict};
+use crate::services::repetition;
+use crate::services::rules::Rules;
+use crate::services::matcher::Matcher;
+use crate::services::short_text;
+use crate::services::stats::TextStats;
+use crate::services::structure;
+use crate::services::stylometry;
+use crate::services::tampering::{self, Normalized};
+use crate::services::{entities, language, text};
+
+#[derive(Debug, Clone)]
 pub struct HeuristicResult {
     pub score: u8,
+    /// Weighted average of all votes before floors and rounding.
+    pub raw_score: f64,
     pub signals: Vec<String>,
+    pub evidence: Vec<Evidence>,
+    pub contributions: Vec<Contribution>,
+    pub floors: Vec<FloorHit>,
+    /// Every measurement taken, voting or not, in the order taken.
+    pub measurements: Vec<(&'static str, f64)>,
+    /// Reliably detected language (ISO 639-1), None for undetermined.
+    pub language: Option<String>,
+    /// False when the detected language has no lexicons and only
+    /// language-neutral signals were used.
+    pub language_supported: bool,
+    /// True when the post was too short for text statistics and was scored on
+    /// short-text features instead.
+    pub short_text: bool,
+    /// Entities cut from the text before the other signals ran.
+    pub removed: Vec<Removed>,
+    /// The post's style, stored for the author's future baselines.
+    pub style: Style,
 }
 
-const FORMULAIC_PHRASES: &[&str] = &[
-    // Classic AI filler
-    "in today's world",
-    "it's important to note",
-    "it is important to note",
-    "it's worth noting",
-    "it is worth noting",
-    "in conclusion",
-    "to sum up",
-    "all things considered",
-    "at the end of the day",
-    "in this article",
-    "here's the thing",
-    "without further ado",
-    "that being said",
-    "having said that",
-    "let's dive in",
-    "dive into",
-    "delve into",
-    "let's explore",
-    "in the world of",
-    "in the realm of",
-    "in light of",
-    "to some extent",
-    "in many cases",
-    "it can be argued",
-    "studies have shown",
-    "experts agree",
-    // Buzzwords
-    "game changer",
-    "game-changer",
-    "cutting-edge",
-    "paradigm shift",
-    "holistic approach",
-    "thought leader",
-    "value proposition",
-    "best practices",
-    "circle back",
-    "unpack this",
-    "at its core",
-    "it goes without saying",
-    "comprehensive guide",
-    "treasure trove",
-    "tapestry of",
-    "daunting task",
-    // AI vocabulary
-    "leverage",
-    "revolutionize",
-    "seamlessly",
-    "furthermore",
-    "moreover",
-    "additionally",
-    "subsequently",
-    "navigate the complexities",
-    "supercharge",
-    "unleash",
-    "unlock",
-    "harness",
-    "robust",
-    "transformative",
-    "synergy",
-    "confluence",
-    "pivotal",
-    "myriad",
-    "plethora",
-    "arguably",
-];
+/// What stored analyses add to a post's own text.
+#[derive(Debug, Default)]
+pub struct Context {
+    /// The author's style baseline, when they have enough history.
+    pub baseline: Option<Baseline>,
+    /// Other accounts that posted near-duplicates; None when the post has no fingerprint.
+    pub template_reuse: Option<usize>,
+}
 
-/// AI-associated standalone words — checked as whole words, case-insensitive.
-const AI_VOCABULARY: &[&str] = &[
-    "underpinning",
-    "trajectory",
-    "spectrum",
-    "facet",
-    "intricacies",
-    "iterative",
-    "nuanced",
-    "holistic",
-    "dynamic",
-    "framework",
-    "comprehensive",
-    "innovative",
-    "bustling",
-    "remarkable",
-    "excitingly",
-    "turbocharging",
-    "unveiling",
-    "harnessing",
-    "revolutionizing",
-    "unleashing",
-    "unlocking",
+/// Lexicons the rules file may define.
+pub const LEXICONS: &[&str] = &[
+    "formulaic_phrases",
+    "ai_vocabulary",
+    "human_slang",
+    "casual_contractions",
+    "promotional_patterns",
+    "calls_to_action",
+    "function_words",
+    "first_person_pronouns",
+    "second_person_pronouns",
+    "first_person_plural_pronouns",
+    "contractions",
+    "generic_replies",
+    "engagement_bait",
 ];
 
-/// Slang / abbreviations that humans use — checked as whole words.
-const HUMAN_SLANG: &[&str] = &[
-    "lol", "lmao", "rofl", "tbh", "fr", "smh", "ngl", "bruh", "bro", "bros", "buddy",
-    "brah", "fam", "dude", "omg", "wtf", "idk", "imo", "imho", "fwiw", "afaik", "btw",
-    "irl", "fomo", "goat", "nah", "yep", "yup", "haha", "hehe", "oops", "ugh", "meh",
-    "pls", "plz", "thx", "ty",
+/// Measurements the rules file may attach signal tiers and floors to.
+pub const MEASUREMENTS: &[&str] = &[
+    "sentence_variance",
+    "type_token_ratio",
+    "burstiness",
+    "formulaic_phrases",
+    "em_en_dash",
+    "spaced_hyphen",
+    "ai_vocabulary",
+    "period_ratio",
+    "comma_ratio",
+    "informality",
+    "linebreak_ratio",
+    "promotional",
+    "word_count",
+    "invisible_chars",
+    "mixed_script_words",
+    "confusable_chars",
+    "unusual_whitespace",
+    "urls",
+    "mentions",
+    "cashtags",
+    "hashtags",
+    "trailing_hashtags",
+    "quoted_lines",
+    "list_density",
+    "emoji_bullets",
+    "title_case_hook",
+    "post_layout",
+    "flesch_kincaid",
+    "gunning_fog",
+    "syllables_per_word",
+    "mean_word_length",
+    "word_length_spread",
+    "function_word_rate",
+    "first_person_share",
+    "second_person_share",
+    "first_person_plural_share",
+    "contraction_rate",
+    "ngram_repetition",
+    "anaphora",
+    "sentence_frames",
+    "generic_praise",
+    "engagement_bait",
+    "emoji_only",
+    "capitalized_sentences",
+    "terminated_sentences",
 ];
 
-/// Casual contractions that signal human writing.
-const CASUAL_CONTRACTIONS: &[&str] = &[
-    "gonna", "wanna", "kinda", "gotta", "dunno", "ain't", "y'all", "can't even",
-    "lowkey", "highkey", "deadass", "legit",
-];
+/// Prior-based weighted vote. Only signals that actually detect something (AI or human)
+/// get to vote; "no evidence" = no vote, so the prior dominates for neutral text.
+struct Scorer<'a> {
+    /// The original text; evidence points into it.
+    text: &'a str,
+    /// The text the signals actually read, mapping back to `text`.
+    normalized: &'a Normalized,
+    rules: &'a Rules,
+    platform: Option<&'a Platform>,
+    votes: Vec<Contribution>,
+    signals: Vec<String>,
+    /// (signal, byte span); turned into `Evidence` once all spans are known.
+    evidence: Vec<(String, Range<usize>)>,
+    measured: Vec<(&'static str, f64)>,
+    /// Floors not applied because the author habitually reaches them.
+    waived: Vec<String>,
+}
 
-/// Promotional / motivational patterns common in AI-generated social media.
-const PROMOTIONAL_PATTERNS: &[&str] = &[
-    // CTAs
-    "link in bio",
-    "link in comments",
-    "link in the comments",
-    "dm me",
-    "follow for more",
-    "comment below",
-    "share this",
-    "tag someone",
-    "save this post",
-    "bookmark this",
-    "check it out",
-    "don't miss out",
-    "sign up",
-    "star if you",
-    "please star",
-    "repost if",
-    "repost this",
-    // Motivational / hustle culture
-    "top 1%",
-    "99% won't",
-    "99% of people",
-    "most people don't",
-    "most people won't",
-    "successful people",
-    "the secret is",
-    "here's what i",
-    "here's how i",
-    "here are the",
-    "stop doing",
-    "start doing",
-    "the truth is",
-    "nobody tells you",
-    "no one tells you",
-    "changed my life",
-    "you need to know",
-    "the hard truth",
-    "key takeaway",
-    "quick thread",
-    "unpopular opinion",
-    "hot take",
-    // Listicle / thread openers
-    "here are",
-    "things i learned",
-    "lessons i learned",
-    "mistakes i made",
-];
+impl<'a> Scorer<'a> {
+    fn new(text: &'a str, normalized: &'a Normalized, rules: &'a Rules, platform: Option<&'a Platform>) -> Self {
+        Self {
+            text,
+            normalized,
+            rules,
+            platform,
+            votes: vec![Contribution {
+                measurement: "prior".to_string(),
+                signal: None,
+                value: None,
+                score: rules.prior.score,
+                weight: rules.prior.weight,
+                share: 0.0,
+            }],
+            signals: Vec::new(),
+            evidence: Vec::new(),
+            measured: Vec::new(),
+            waived: Vec::new(),
+        }
+    }
+
+    /// Record a measurement and let its first matching tier vote. Returns whether a tier matched.
+    fn measure(&mut self, name: &'static str, value: f64) -> bool {
+        self.measure_spans(name, value, Vec::new())
+    }
 
-pub fn analyze(text: &str) -> HeuristicResult {
-    let mut signals = Vec::new();
-
-    // Prior-based scoring: start with a human-leaning base.
-    // Only signals that actually detect something (AI or human) get to vote.
-    // "No evidence" = no vote, so the prior dominates for neutral text.
-    let mut score_sum: f64 = 3.0 * 1.5; // human-leaning prior, light weight so signals dominate
-    let mut weight_sum: f64 = 1.5;
-
-    // 1. Sentence length variance (AI tends to write uniform sentence lengths)
-    let sentence_variance = sentence_length_variance(text);
-    if sentence_variance < 5.0 {
-        signals.push("uniform_sentence_length".to_string());
-        score_sum += 8.0 * 2.0;
-        weight_sum += 2.0;
-    } else if sentence_variance < 15.0 {
-        signals.push("low_sentence_variance".to_string());
-        score_sum += 5.0 * 1.5;
-        weight_sum += 1.5;
-    } else if sentence_variance > 50.0 {
-        // Very varied = weak human signal (shouldn't overpower hard AI evidence)
-        score_sum += 2.0 * 0.5;
-        weight_sum += 0.5;
-    }
-    // 15-50: no opinion, skip
-
-    // 2. Vocabulary diversity (Type-Token Ratio)
-    let ttr = type_token_ratio(text);
-    if ttr < 0.4 {
-        signals.push("low_vocabulary_diversity".to_string());
-        score_sum += 7.0 * 1.5;
-        weight_sum += 1.5;
-    } else if ttr >= 0.55 {
-        // Diverse vocab = weak human signal
-        score_sum += 2.0 * 0.5;
-        weight_sum += 0.5;
-    }
-    // 0.4-0.55: neutral, skip
-
-    // 3. Burstiness (AI text tends to have low burstiness — uniform flow)
-    let burstiness = compute_burstiness(text);
-    if burstiness < 0.3 {
-        signals.push("low_burstiness".to_string());
-        score_sum += 7.0 * 1.5;
-        weight_sum += 1.5;
-    } else if burstiness >= 0.5 {
-        // Bursty = weak human signal
-        score_sum += 2.0 * 0.5;
-        weight_sum += 0.5;
-    }
-    // 0.3-0.5: neutral, skip
-
-    // 4. Formulaic phrase detection (strong AI signal when present)
-    let formula_count = count_formulaic_phrases(text);
-    if formula_count >= 3 {
-        signals.push("formulaic_phrases".to_string());
-        score_sum += 9.0 * 3.0;
-        weight_sum += 3.0;
-    } else if formula_count >= 1 {
-        signals.push("some_formulaic_phrases".to_string());
-        score_sum += 6.0 * 2.0;
-        weight_sum += 2.0;
-    }
-    // 0: no phrases found, skip — absence doesn't prove human
-
-    // 5. Dash detection — split by type:
-    //    Em/en dashes (—/–): near-definitive AI, humans don't type these
-    //    Spaced hyphens (" - "): ~90% AI, humans write "like this or" not "like this - or"
-    let (unicode_dashes, spaced_hyphens) = count_dashes_split(text);
-    if unicode_dashes >= 1 {
-        signals.push("em_en_dash".to_string());
-        // Almost certain AI — even 1 em dash is a dead giveaway
-        score_sum += 9.0 * 5.0;
-        weight_sum += 5.0;
-    }
-    if spaced_hyphens >= 1 {
-        signals.push("spaced_hyphen".to_string());
-        score_sum += 8.0 * 2.5;
-        weight_sum += 2.5;
-    }
-    // 0: skip
-
-    // 6. AI vocabulary words (standalone words, not just phrases)
-    let ai_word_count = count_ai_vocabulary(text);
-    if ai_word_count >= 3 {
-        signals.push("ai_vocabulary".to_string());
-        score_sum += 8.0 * 2.0;
-        weight_sum += 2.0;
-    } else if ai_word_count >= 1 {
-        signals.push("some_ai_vocabulary".to_string());
-        score_sum += 6.0 * 1.5;
-        weight_sum += 1.5;
-    }
-    // 0: skip
-
-    // 7. Punctuation patterns (AI uses more consistent punctuation)
-    let punct_result = punctuation_analysis(text, &mut signals);
-    if let Some(ps) = punct_result {
-        score_sum += ps * 1.0;
-        weight_sum += 1.0;
-    }
-
-    // 8. Human informality markers (slang, casual language, !! / ??)
-    let informality = count_informality(text);
-    if informality >= 3 {
-        signals.push("informal_language".to_string());
-        score_sum += 1.0 * 3.0;
-        weight_sum += 3.0;
-    } else if informality >= 1 {
-        signals.push("some_informal_markers".to_string());
-        score_sum += 2.0 * 2.0;
-        weight_sum += 2.0;
-    }
-    // 0: formal writing is ambiguous — many humans write formally. Skip.
-
-    // 9. Line-break heavy formatting (LinkedIn AI: one sentence per line)
-    let lb_ratio = linebreak_ratio(text);
-    if lb_ratio > 0.8 {
-        signals.push("line_per_sentence".to_string());
-        score_sum += 8.0 * 2.5;
-        weight_sum += 2.5;
-    } else if lb_ratio > 0.5 {
-        signals.push("heavy_line_breaks".to_string());
-        score_sum += 7.0 * 2.0;
-        weight_sum += 2.0;
-    }
-    // low: skip
-
-    // 10. Promotional / motivational patterns (social media AI)
-    let promo_count = count_promotional(text);
-    if promo_count >= 2 {
-        signals.push("promotional_pattern".to_string());
-        score_sum += 9.0 * 2.5;
-        weight_sum += 2.5;
-    } else if promo_count >= 1 {
-        signals.push("some_promotional".to_string());
-        score_sum += 6.0 * 1.5;
-        weight_sum += 1.5;
-    }
-    // 0: skip
-
-    // 11. Text too short for reliable analysis
-    let word_count = text.split_whitespace().count();
-    if word_count < 20 {
-        signals.push("short_text_low_confidence".to_string());
-    }
-
-    let mut final_score = (score_sum / weight_sum).round() as u8;
-
-    // Em dashes are definitive AI — enforce minimum score
-    if unicode_dashes >= 1 && final_score < 8 {
-        final_score = 8;
-    }
-    // Spaced hyphens (" - "): 2+ is strong AI, enforce floor
-    if spaced_hyphens >= 2 && final_score < 7 {
-        final_score = 7;
-    }
-
-    HeuristicResult {
-        score: final_score.min(10),
-        signals,
+    /// Like `measure`, attaching the byte spans (into the normalized text) that
+    /// produced the value as evidence for the signal that fires.
+    fn measure_spans(&mut self, name: &'static str, value: f64, spans: Vec<Range<usize>>) -> bool {
+        let spans = spans.into_iter().map(|span| self.normalized.span(span)).collect();
+        self.measure_source_spans(name, value, spans)
+    }
+
+    /// Like `measure_spans`, for spans that already point into the original text.
+    fn measure_source_spans(&mut self, name: &'static str, value: f64, spans: Vec<Range<usize>>) -> bool {
+        self.measured.push((name, value));
+        let Some(tier) = self.rules.tiers_for(self.platform, name).iter().find(|t| t.matches(value)) else {
+            return false;
+        };
+        if let Some(signal) = &tier.signal {
+            self.signals.push(signal.clone());
+            self.evidence
+                .extend(spans.into_iter().map(|span| (signal.clone(), span)));
+        }
+        self.votes.push(Contribution {
+            measurement: name.to_string(),
+            signal: tier.signal.clone(),
+            value: Some(value),
+            score: tier.score,
+            weight: tier.weight * self.rules.weight_scale(self.platform, name),
+            share: 0.0,
+        });
+        true
+    }
+
+    fn finish(self) -> HeuristicResult {
+        let mut votes = self.votes;
+        let weight_sum: f64 = votes.iter().map(|v| v.weight).sum();
+        for vote in &mut votes {
+            vote.share = vote.score * vote.weight / weight_sum;
+        }
+        let raw_score = votes.iter().map(|v| v.share).sum::<f64>();
+
+        // Hard floors (e.g. em dashes are definitive AI)
+        let mut floors: Vec<FloorHit> = self
+            .rules
+            .floors
+            .iter()
+            .filter(|floor| !self.waived.contains(&floor.measurement))
+            .filter(|floor| {
+                self.measured
+                    .iter()
+                    .any(|(name, value)| *name == floor.measurement && *value >= floor.at_least)
+            })
+            .map(|floor| FloorHit {
+                measurement: floor.measurement.clone(),
+                score: floor.score,
+                applied: false,
+            })
+            .collect();
+        let score = combine(&votes, &floors, None);
+        let unfloored = combine(&votes, &[], None);
+        for floor in &mut floors {
+            floor.applied = floor.score == score && floor.score > unfloored;
+        }
+
+        // Char offsets in one sweep over the sorted spans instead of recounting from 0
+        let mut spans = self.evidence;
+        spans.sort_by_key(|(_, span)| (span.start, span.end));
+        let (mut byte, mut chars) = (0, 0);
+        let evidence = spans
+            .into_iter()
+            .map(|(signal, span)| {
+                chars += self.text[byte..span.start].chars().count();
+                byte = span.start;
+                Evidence::new(&signal, self.text, span, chars)
+            })
+            .collect();
+
+        HeuristicResult {
+            score,
+            raw_score,
+            signals: self.signals,
+            evidence,
+            contributions: votes,
+            floors,
+            measurements: self.measured,
+            language: None,
+            language_supported: true,
+            short_text: false,
+            removed: Vec::new(),
+            style: Style::default(),
+        }
     }
 }
 
-fn sentence_length_variance(text: &str) -> f64 {
-    let sentences: Vec<&str> = text
-        .split(|c: char| c == '.' || c == '!' || c == '?')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
+/// Final score from the votes and the floors that were reached, optionally leaving
+/// out one measurement's vote and floors.
+fn combine(votes: &[Contribution], floors: &[FloorHit], without: Option<&str>) -> u8 {
+    let kept = || votes.iter().filter(|v| Some(v.measurement.as_str()) != without);
+    let weight_sum: f64 = kept().map(|v| v.weight).sum();
+    let mut score = if weight_sum > 0.0 {
+        (kept().map(|v| v.score * v.weight).sum::<f64>() / weight_sum).round() as u8
+    } else {
+        0
+    };
+    for floor in floors {
+        if Some(floor.measurement.as_str()) != without && score < floor.score {
+            score = floor.score;
+        }
+    }
+    score.min(10)
+}
 
-    if sentences.len() < 3 {
-        return 50.0; // Not enough sentences to judge
+/// What the heuristic score would be with each vote removed (along with any floor
+/// its measurement triggered), and with each reached floor lifted on its own.
+pub fn counterfactuals(result: &HeuristicResult) -> Vec<Counterfactual> {
+    let mut out = Vec::new();
+
+    for vote in result.contributions.iter().filter(|v| v.value.is_some()) {
+        let score = combine(&result.contributions, &result.floors, Some(&vote.measurement));
+        let name = vote.signal.as_deref().unwrap_or(&vote.measurement);
+        out.push(Counterfactual {
+            removed: "signal".to_string(),
+            measurement: vote.measurement.clone(),
+            signal: vote.signal.clone(),
+            score,
+            delta: score as i32 - result.score as i32,
+            description: format!("without {name} this would be {score}"),
+        });
+    }
+
+    for (i, floor) in result.floors.iter().enumerate() {
+        let others: Vec<FloorHit> = result
+            .floors
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, f)| f.clone())
+            .collect();
+        let score = combine(&result.contributions, &others, None);
+        out.push(Counterfactual {
+            removed: "floor".to_string(),
+            measurement: floor.measurement.clone(),
+            signal: None,
+            score,
+            delta: score as i32 - result.score as i32,
+            description: format!("without the {} floor this would be {score}", floor.measurement),
+        });
+    }
+
+    out
+}
+
+/// The heuristic engine as an ensemble detector.
+pub struct Heuristics;
+
+#[async_trait]
+impl Detector for Heuristics {
+    fn name(&self) -> &'static str {
+        "heuristics"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let result = input.analysis().await?.result.clone();
+        Ok(Verdict {
+            score: result.score,
+            confidence: ensemble::CONFIDENCE,
+            signals: result.signals.clone(),
+            evidence: result.evidence.clone(),
+            heuristics: Some(result),
+            ..Default::default()
+        })
+    }
+}
+
+/// Score `text` with the global rules, adjusted by the profile of `platform` if it has one.
+pub fn analyze(text: &str, rules: &Rules, platform: Option<&Platform>) -> HeuristicResult {
+    analyze_in_context(text, rules, platform, &Context::default())
+}
+
+/// Like `analyze`, also comparing the post with the author's baseline and
+/// other accounts' posts in `context`.
+pub fn analyze_in_context(text: &str, rules: &Rules, platform: Option<&Platform>, context: &Context) -> HeuristicResult {
+    Analysis::new(text, rules, platform, context).result
+}
+
+/// A post's heuristic result with the text its signals read, so detectors that
+/// need the cleaned text or its sentences don't normalize and segment it again.
+pub struct Analysis {
+    pub result: HeuristicResult,
+    /// The post with tampering undone and entities cut, mapping back to the post.
+    pub content: Normalized,
+    /// Byte ranges of the sentences of `content.text`.
+    pub sentences: Vec<Range<usize>>,
+}
+
+impl Analysis {
+    pub fn new(text: &str, rules: &Rules, platform: Option<&Platform>, context: &Context) -> Self {
+        // 0. Tampering: record humanizer tricks (invisible characters, homoglyphs,
+        //    lookalike dashes, exotic spaces) and undo them.
+        let normalized = tampering::normalize(text);
+
+        // Entities: links, mentions, cashtags, hashtags and quoted text are counted,
+        // then cut. Every later signal reads what is left.
+        let found = entities::find(&normalized.text);
+        let content = normalized.without(&found.cuts());
+        let removed = found
+            .all()
+            .into_iter()
+            .map(|(kind, span)| Removed::new(kind, text, normalized.span(span)))
+            .collect();
+
+        let mut scorer = Scorer::new(text, &content, rules, platform);
+        let findings = &normalized.findings;
+        for (name, spans) in [
+            ("invisible_chars", &findings.invisible),
+            ("mixed_script_words", &findings.mixed_script),
+            ("confusable_chars", &findings.confusables),
+            ("unusual_whitespace", &findings.whitespace),
+        ] {
+            scorer.measure_source_spans(name, spans.len() as f64, spans.clone());
+        }
+        let source = |spans: &[Range<usize>]| spans.iter().map(|s| normalized.span(s.clone())).collect();
+        let hashtags = [found.hashtags.as_slice(), &found.inline_hashtags].concat();
+        for (name, value, spans) in [
+            ("urls", found.urls.len(), source(&found.urls)),
+            ("mentions", found.mentions.len(), source(&found.mentions)),
+            ("cashtags", found.cashtags.len(), source(&found.cashtags)),
+            ("hashtags", found.hashtag_count(), source(&hashtags)),
+            ("trailing_hashtags", found.trailing_hashtags, Vec::new()),
+            ("quoted_lines", found.quoted.len(), Vec::new()),
+        ] {
+            scorer.measure_source_spans(name, value as f64, spans);
+        }
+        let text = content.text.as_str();
+
+        // Language: pick lexicons. Undetermined (usually short) text uses the default
+        // language; a detected language without lexicons gets language-neutral signals only.
+        let stats = TextStats::new(text);
+        let language = language::detect(text);
+        let lexicon_language = match language.as_deref() {
+            Some(lang) if rules.has_lexicons(lang) => Some(lang),
+            Some(_) => None,
+            None => Some(rules.language.default.as_str()),
+        };
+        let lexicon = |name| rules.matcher(lexicon_language, name);
+
+        let short = stats.word_count() < rules.short_text.below;
+
+        // 1. Formulaic phrase detection (strong AI signal when present)
+        let spans = find_lexicon(&stats, lexicon("formulaic_phrases"));
+        scorer.measure_spans("formulaic_phrases", spans.len() as f64, spans);
+
+        // 2. Dash detection — split by type:
+        //    Em/en dashes (—/–): near-definitive AI, humans don't type these
+        //    Spaced hyphens (" - "): ~90% AI, humans write "like this or" not "like this - or"
+        let (unicode_dashes, spaced_hyphens) = find_dashes_split(text);
+        scorer.measure_spans("em_en_dash", unicode_dashes.len() as f64, unicode_dashes);
+        scorer.measure_spans("spaced_hyphen", spaced_hyphens.len() as f64, spaced_hyphens);
+
+        // 3. AI vocabulary words (standalone words, not just phrases)
+        let spans = find_lexicon(&stats, lexicon("ai_vocabulary"));
+        scorer.measure_spans("ai_vocabulary", spans.len() as f64, spans);
+
+        // 4. Human informality markers (slang, casual language, !! / ??)
+        let (informality, spans) =
+            find_informality(&stats, lexicon("human_slang"), lexicon("casual_contractions"));
+        scorer.measure_spans("informality", informality as f64, spans);
+
+        // 5. Promotional / motivational patterns (social media AI)
+        let spans = find_lexicon(&stats, lexicon("promotional_patterns"));
+        scorer.measure_spans("promotional", spans.len() as f64, spans);
+
+        // 6. Short posts get features that work at reply length; longer ones get
+        //    the text statistics, which need dozens of words to mean anything
+        if short {
+            measure_short_text(&mut scorer, &stats, &lexicon);
+        } else {
+            measure_statistics(&mut scorer, &stats, &lexicon, lexicon_language == Some("en"));
+        }
+
+        // 7. Text too short for reliable analysis
+        scorer.measure("word_count", stats.word_count() as f64);
+
+        // 8. Author baseline: distance from the author's own style, and floors
+        //    that are their habit rather than evidence
+        let style = Style {
+            features: baseline::features(&stats, &scorer.measured),
+            floors: baseline::floors_reached(rules, &scorer.measured),
+        };
+        let author = &rules.author_baseline;
+        if let Some(baseline) = context.baseline.as_ref().filter(|b| b.posts >= author.min_posts) {
+            if let Some(deviation) = baseline.deviation(&style.features, author.min_posts) {
+                scorer.measure("author_deviation", deviation);
+            }
+            scorer.waived = baseline.habits(&author.waive_floors, author.habitual);
+            scorer.waived.retain(|m| style.floors.contains(m));
+            scorer.signals.extend(scorer.waived.iter().map(|m| format!("habitual_{m}")));
+        }
+
+        // 9. Template reuse: other accounts posting near-duplicates of this post
+        if let Some(reuse) = context.template_reuse {
+            scorer.measure("template_reuse", reuse as f64);
+        }
+
+        let language_supported = language.is_none() || lexicon_language.is_some();
+        let sentences = stats.sentences.iter().map(|s| s.start..s.start + s.text.len()).collect();
+        let result = HeuristicResult {
+            language,
+            language_supported,
+            short_text: short,
+            removed,
+            style,
+            ..scorer.finish()
+        };
+        Self {
+            result,
+            content,
+            sentences,
+        }
+    }
+}
+
+/// Text statistics, layout, stylometry and repetition, for posts long enough to measure.
+fn measure_statistics<'a>(
+    scorer: &mut Scorer<'a>,
+    stats: &TextStats,
+    lexicon: &impl Fn(&'static str) -> Option<&'a Matcher>,
+    english: bool,
+) {
+    // Sentence length variance (AI tends to write uniform sentence lengths)
+    scorer.measure("sentence_variance", sentence_length_variance(stats));
+
+    // Vocabulary diversity (Type-Token Ratio)
+    scorer.measure("type_token_ratio", type_token_ratio(stats));
+
+    // Burstiness (AI text tends to have low burstiness — uniform flow)
+    scorer.measure("burstiness", compute_burstiness(stats));
+
+    // Punctuation patterns (AI uses more consistent punctuation)
+    if let Some((period_ratio, comma_ratio)) = punctuation_ratios(stats) {
+        // Almost all periods wins over comma frequency
+        if !scorer.measure("period_ratio", period_ratio) {
+            if let Some(comma_ratio) = comma_ratio {
+                scorer.measure("comma_ratio", comma_ratio);
+            }
+        }
+    }
+
+    // Line-break heavy formatting (LinkedIn AI: one sentence per line)
+    scorer.measure("linebreak_ratio", linebreak_ratio(stats));
+
+    // Post structure (LinkedIn AI template: Title Case hook, bullet lists with
+    // emoji bullets, closing call to action)
+    let layout = structure::analyze(stats, lexicon("calls_to_action"));
+    scorer.measure_spans("list_density", layout.list_density, layout.list_markers);
+    scorer.measure_spans("emoji_bullets", layout.emoji_bullets.len() as f64, layout.emoji_bullets);
+    scorer.measure_spans(
+        "title_case_hook",
+        layout.title_case_hook.is_some() as u8 as f64,
+        layout.title_case_hook.into_iter().collect(),
+    );
+    scorer.measure_spans("post_layout", layout.layout.parts() as f64, layout.layout.spans());
+
+    // Stylometry (readability, word shape, function words, pronoun profile,
+    // contractions); each feature is skipped when it can't be measured
+    let style = stylometry::analyze(
+        stats,
+        &stylometry::Lexicons {
+            function_words: lexicon("function_words"),
+            first_person: lexicon("first_person_pronouns"),
+            second_person: lexicon("second_person_pronouns"),
+            first_person_plural: lexicon("first_person_plural_pronouns"),
+            contractions: lexicon("contractions"),
+        },
+        english,
+    );
+    for (name, value) in [
+        ("flesch_kincaid", style.flesch_kincaid),
+        ("gunning_fog", style.gunning_fog),
+        ("syllables_per_word", style.syllables_per_word),
+        ("mean_word_length", style.mean_word_length),
+        ("word_length_spread", style.word_length_spread),
+        ("function_word_rate", style.function_word_rate),
+        ("first_person_share", style.first_person_share),
+        ("second_person_share", style.second_person_share),
+        ("first_person_plural_share", style.first_person_plural_share),
+        ("contraction_rate", style.contraction_rate),
+    ] {
+        if let Some(value) = value {
+            scorer.measure(name, value);
+        }
     }
 
-    let lengths: Vec<f64> = sentences
+    // Repetition inside the post (echoed phrases, "The 1% ... The 1% ..."
+    // openings, sentences cut from one template)
+    let repeats = repetition::analyze(stats);
+    scorer.measure_spans("ngram_repetition", repeats.ngram_repetition, repeats.repeated_phrases);
+    scorer.measure_spans("anaphora", repeats.anaphora as f64, repeats.anaphora_openings);
+    scorer.measure_spans("sentence_frames", repeats.frames.len() as f64, repeats.frames);
+}
+
+/// Stock replies, emoji-only reactions, engagement bait, and casing and
+/// punctuation habits, for posts too short for statistics.
+fn measure_short_text<'a>(
+    scorer: &mut Scorer<'a>,
+    stats: &TextStats,
+    lexicon: &impl Fn(&'static str) -> Option<&'a Matcher>,
+) {
+    let short = short_text::analyze(
+        stats,
+        &short_text::Lexicons {
+            generic_replies: lexicon("generic_replies"),
+            engagement_bait: lexicon("engagement_bait"),
+        },
+    );
+    if let Some(praise) = short.generic_praise {
+        scorer.measure_spans("generic_praise", praise, short.generic_phrases);
+    }
+    scorer.measure_spans("engagement_bait", short.engagement_bait.len() as f64, short.engagement_bait);
+    scorer.measure_spans("emoji_only", !short.emoji_only.is_empty() as u8 as f64, short.emoji_only);
+    for (name, value) in [
+        ("capitalized_sentences", short.capitalized),
+        ("terminated_sentences", short.terminated),
+    ] {
+        if let Some(value) = value {
+            scorer.measure(name, value);
+        }
+    }
+}
+
+/// Word counts of prose sentences. List items and unpunctuated standalone lines
+/// ("$15k today") are fragments, not sentences, and would make any line-formatted
+/// post look uniform; line structure is measured by `linebreak_ratio` instead.
+fn sentence_lengths(stats: &TextStats) -> Vec<f64> {
+    stats
+        .sentences
         .iter()
-        .map(|s| s.split_whitespace().count() as f64)
-        .collect();
+        .zip(&stats.sentence_words)
+        .filter(|(s, _)| !s.list_item && (s.terminator.is_some() || !s.own_line))
+        .map(|(_, words)| *words as f64)
+        .collect()
+}
+
+fn sentence_length_variance(stats: &TextStats) -> f64 {
+    let lengths = sentence_lengths(stats);
+
+    if lengths.len() < 3 {
+        return 50.0; // Not enough sentences to judge
+    }
 
     let mean = lengths.iter().sum::<f64>() / lengths.len() as f64;
     lengths.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / lengths.len() as f64
 }
 
-fn type_token_ratio(text: &str) -> f64 {
-    let words: Vec<String> = text
-        .split_whitespace()
-        .map(|w| w.to_lowercase().trim_matches(|c: char| !c.is_alphanumeric()).to_string())
-        .filter(|w| !w.is_empty())
-        .collect();
-
-    if words.is_empty() {
+fn type_token_ratio(stats: &TextStats) -> f64 {
+    if stats.word_count() == 0 {
         return 1.0;
     }
 
-    let unique: HashSet<&String> = words.iter().collect();
-    unique.len() as f64 / words.len() as f64
+    let unique: HashSet<&str> = stats.lower_words().map(|(_, w)| w).collect();
+    unique.len() as f64 / stats.word_count() as f64
 }
 
-fn compute_burstiness(text: &str) -> f64 {
-    let sentences: Vec<&str> = text
-        .split(|c: char| c == '.' || c == '!' || c == '?')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
+fn compute_burstiness(stats: &TextStats) -> f64 {
+    let lengths = sentence_lengths(stats);
 
-    if sentences.len() < 3 {
+    if lengths.len() < 3 {
         return 0.5;
     }
 
-    let lengths: Vec<f64> = sentences
-        .iter()
-        .map(|s| s.split_whitespace().count() as f64)
-        .collect();
-
     let mean = lengths.iter().sum::<f64>() / lengths.len() as f64;
     if mean == 0.0 {
         return 0.5;
@@ -389,145 +638,112 @@ fn compute_burstiness(text: &str) -> f64 {
     (raw + 1.0) / 2.0 // Normalize from [-1,1] to [0,1]
 }
 
-fn count_formulaic_phrases(text: &str) -> usize {
-    let lower = text.to_lowercase();
-    FORMULAIC_PHRASES
-        .iter()
-        .filter(|phrase| lower.contains(**phrase))
-        .count()
+/// Span of every whole-word occurrence of a lexicon entry; the count is the signal value.
+fn find_lexicon(stats: &TextStats, matcher: Option<&Matcher>) -> Vec<Range<usize>> {
+    let Some(matcher) = matcher else {
+        return Vec::new();
+    };
+    matcher
+        .find(&stats.lower.text)
+        .into_iter()
+        .map(|(_, span)| stats.lower.span(span.start, span.end))
+        .collect()
 }
 
-/// Returns (unicode_dashes, spaced_hyphens) counted separately.
+/// Returns spans of (unicode_dashes, spaced_hyphens) found separately.
 /// Unicode em/en dashes are near-definitive AI. Spaced hyphens are strong AI indicators.
-fn count_dashes_split(text: &str) -> (usize, usize) {
-    let mut unicode = 0;
-    for ch in text.chars() {
-        if ch == '\u{2014}' || ch == '\u{2013}' {
-            unicode += 1;
-        }
-    }
-    let spaced = text.matches(" - ").count() + text.matches(" -- ").count();
+fn find_dashes_split(text: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let unicode = text
+        .char_indices()
+        .filter(|(_, ch)| *ch == '\u{2014}' || *ch == '\u{2013}')
+        .map(|(i, ch)| i..i + ch.len_utf8())
+        .collect();
+    // Span covers the hyphen(s) only, not the surrounding spaces
+    let spaced = text
+        .match_indices(" - ")
+        .chain(text.match_indices(" -- "))
+        .map(|(i, m)| i + 1..i + m.len() - 1)
+        .collect();
     (unicode, spaced)
 }
 
-fn count_ai_vocabulary(text: &str) -> usize {
-    let lower = text.to_lowercase();
-    let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
-    AI_VOCABULARY
-        .iter()
-        .filter(|vocab| words.iter().any(|w| *w == **vocab))
-        .count()
-}
-
-/// Returns Some(score) if a punctuation signal was detected, None if neutral.
-fn punctuation_analysis(text: &str, signals: &mut Vec<String>) -> Option<f64> {
-    let sentences: Vec<&str> = text
-        .split(|c: char| c == '.' || c == '!' || c == '?')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    if sentences.len() < 3 {
+/// Returns (period share of sentence terminators, commas per word), or None when
+/// there are too few sentences to judge. Only punctuation that actually ends a
+/// sentence counts, so "$1.5M" or "e.g." don't inflate the period share. The
+/// comma ratio is None for text without words.
+fn punctuation_ratios(stats: &TextStats) -> Option<(f64, Option<f64>)> {
+    if stats.sentences.len() < 3 {
         return None;
     }
 
-    let total_terminators = text.chars().filter(|c| *c == '.' || *c == '!' || *c == '?').count();
-    if total_terminators == 0 {
+    let terminators: Vec<char> = stats.sentences.iter().filter_map(|s| s.terminator).collect();
+    if terminators.is_empty() {
         return None;
     }
 
-    // Almost all periods = uniform punctuation (AI signal)
-    let period_ratio = text.chars().filter(|c| *c == '.').count() as f64 / total_terminators as f64;
-    if period_ratio > 0.95 {
-        signals.push("uniform_punctuation".to_string());
-        return Some(6.0);
-    }
+    let periods = terminators.iter().filter(|c| text::PERIODS.contains(c)).count();
+    let period_ratio = periods as f64 / terminators.len() as f64;
 
-    // High comma frequency (AI signal)
-    let comma_count = text.chars().filter(|c| *c == ',').count();
-    let word_count = text.split_whitespace().count();
-    if word_count > 0 {
-        let comma_ratio = comma_count as f64 / word_count as f64;
-        if comma_ratio > 0.15 {
-            signals.push("high_comma_frequency".to_string());
-            return Some(6.0);
-        }
-    }
+    let word_count = stats.word_count();
+    let comma_ratio = (word_count > 0).then(|| stats.commas as f64 / word_count as f64);
 
-    None // neutral punctuation, no vote
+    Some((period_ratio, comma_ratio))
 }
 
-/// Count human informality markers: slang, casual contractions, repeated punctuation.
-fn count_informality(text: &str) -> usize {
-    let lower = text.to_lowercase();
-    let words: Vec<&str> = lower
-        .split(|c: char| !c.is_alphanumeric() && c != '\'')
-        .filter(|w| !w.is_empty())
-        .collect();
-    let mut count = 0;
-
-    // Slang / abbreviations (whole word match)
-    for slang in HUMAN_SLANG {
-        if words.iter().any(|w| *w == *slang) {
-            count += 1;
-        }
-    }
-
-    // Casual contractions (substring match — "gonna", "kinda", etc.)
-    for contraction in CASUAL_CONTRACTIONS {
-        if lower.contains(contraction) {
-            count += 1;
-        }
-    }
+/// Count human informality markers: every slang word and casual contraction, plus
+/// one each for repeated `!!`/`??` and ellipses. Returns the count plus the span of
+/// every occurrence.
+fn find_informality(
+    stats: &TextStats,
+    slang_words: Option<&Matcher>,
+    contractions: Option<&Matcher>,
+) -> (usize, Vec<Range<usize>>) {
+    let text = stats.text;
+
+    // Slang / abbreviations ("tbh", "lol") and casual contractions ("gonna", "kinda")
+    let mut spans = find_lexicon(stats, slang_words);
+    spans.extend(find_lexicon(stats, contractions));
+    let mut count = spans.len();
 
     // Repeated punctuation (!!, ??, ...)
-    if text.contains("!!") || text.contains("??") {
+    let repeated: Vec<Range<usize>> = text
+        .match_indices("!!")
+        .chain(text.match_indices("??"))
+        .map(|(i, m)| i..i + m.len())
+        .collect();
+    if !repeated.is_empty() {
         count += 1;
+        spans.extend(repeated);
     }
-    if text.contains("...") {
+    let ellipses: Vec<Range<usize>> = text.match_indices("...").map(|(i, m)| i..i + m.len()).collect();
+    if !ellipses.is_empty() {
         count += 1;
+        spans.extend(ellipses);
     }
 
-    count
+    (count, spans)
 }
 
-/// Ratio of non-empty lines to sentences — high ratio = one sentence per line (LinkedIn AI).
-fn linebreak_ratio(text: &str) -> f64 {
-    let lines: Vec<&str> = text
-        .split('\n')
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty())
-        .collect();
-
-    if lines.len() < 3 {
+/// Share of sentences standing alone on their own line — high = one sentence per line (LinkedIn AI).
+fn linebreak_ratio(stats: &TextStats) -> f64 {
+    let sentences = &stats.sentences;
+    if stats.lines.len() < 3 || sentences.is_empty() {
         return 0.0; // too few lines to judge
     }
 
-    let sentences: Vec<&str> = text
-        .split(|c: char| c == '.' || c == '!' || c == '?')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
-
-    if sentences.is_empty() {
-        return 0.0;
-    }
-
-    lines.len() as f64 / sentences.len().max(1) as f64
-}
-
-/// Count promotional / motivational patterns common in AI social media posts.
-fn count_promotional(text: &str) -> usize {
-    let lower = text.to_lowercase();
-    PROMOTIONAL_PATTERNS
-        .iter()
-        .filter(|p| lower.contains(**p))
-        .count()
+    sentences.iter().filter(|s| s.own_line).count() as f64 / sentences.len() as f64
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::LazyLock;
+
+    static RULES: LazyLock<Rules> = LazyLock::new(Rules::builtin);
+
+    fn analyze(text: &str) -> HeuristicResult {
+        super::analyze(text, &RULES, None)
+    }
 
     #[test]
     fn test_human_text() {
@@ -713,6 +929,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_short_text_path() {
+        let result = analyze("Great insights! Thanks for sharing.");
+        assert!(result.short_text);
+        assert!(result.signals.iter().any(|s| s == "generic_reply"), "{:?}", result.signals);
+        assert!(!result.contributions.iter().any(|c| c.measurement == "sentence_variance"));
+        assert!(result.score > analyze("lol my cat did the exact same thing").score);
+
+        let result = analyze("@bob 🔥🔥🙌");
+        assert!(result.signals.iter().any(|s| s == "emoji_only_reply"), "{:?}", result.signals);
+        let result = analyze("Who else is tired of pointless meetings?");
+        assert!(result.signals.iter().any(|s| s == "engagement_bait"), "{:?}", result.signals);
+
+        let casual = analyze("ok this is actually hilarious");
+        assert!(casual.signals.iter().any(|s| s == "lowercase_casing"), "{:?}", casual.signals);
+        assert!(casual.signals.iter().any(|s| s == "unpunctuated"), "{:?}", casual.signals);
+        assert!(casual.score <= 3);
+    }
+
     #[test]
     fn test_em_dash_flags_ai() {
         // Em dash is a definitive AI marker — must score 8+
@@ -737,4 +972,250 @@ mod tests {
             "Multi-spaced-hyphen post should score likely_ai (>=7), got {} (signals: {:?})",
             result.score, result.signals);
     }
+
+    #[test]
+    fn test_evidence_spans_point_at_triggers() {
+        let text = "Ünïcode first — then we leverage it. Furthermore, lol.";
+        let result = analyze(text);
+
+        let dash = result.evidence.iter().find(|e| e.signal == "em_en_dash").unwrap();
+        assert_eq!(dash.text, "—");
+        assert_eq!(&text[dash.start..dash.end], "—");
+        assert_eq!(text.chars().nth(dash.char_start), Some('—'));
+        assert_eq!(dash.char_end, dash.char_start + 1);
+
+        let phrases: Vec<&str> = result
+            .evidence
+            .iter()
+            .filter(|e| e.signal == "some_formulaic_phrases")
+            .map(|e| e.text.as_str())
+            .collect();
+        assert_eq!(phrases, ["leverage", "Furthermore"]);
+
+        assert!(result.evidence.iter().any(|e| e.signal == "some_informal_markers" && e.text == "lol"));
+    }
+
+    #[test]
+    fn test_author_habits_waive_floors() {
+        let text = "went to the market this morning — the tomatoes are finally back. \
+                    picked up way too many, gonna make sauce tonight i think";
+        let alone = analyze(text);
+        assert!(alone.floors.iter().any(|f| f.measurement == "em_en_dash"));
+        assert_eq!(alone.style.floors, ["em_en_dash"]);
+
+        // Five past posts with dashes: the dash is this author's habit
+        let history = vec![alone.style.clone(); 5];
+        let baseline = Baseline::new(&history);
+        let context = Context {
+            baseline: Some(baseline),
+            template_reuse: None,
+        };
+        let result = super::analyze_in_context(text, &RULES, None, &context);
+        assert!(result.floors.is_empty());
+        assert!(result.signals.contains(&"habitual_em_en_dash".to_string()));
+        assert!(result.score < alone.score, "{} vs {}", result.score, alone.score);
+        assert!(result.measurements.iter().any(|(name, _)| *name == "author_deviation"));
+        assert_eq!(result.style.floors, ["em_en_dash"], "a waived floor is still recorded");
+    }
+
+    #[test]
+    fn test_contributions_and_counterfactuals() {
+        let text = ".@tensol_ai turns OpenClaw into full-time AI employees for your company. \
+                    They handle repetitive workflows across support, engineering, sales and more \
+                    — running 24/7 in a secure environment, connected to your tools, with full \
+                    context of your business. Congrats on the launch";
+        let result = analyze(text);
+
+        let shares: f64 = result.contributions.iter().map(|c| c.share).sum();
+        assert!((shares - result.raw_score).abs() < 1e-9);
+        assert!(result.contributions.iter().any(|c| c.signal.as_deref() == Some("em_en_dash")));
+
+        let floor = result.floors.iter().find(|f| f.measurement == "em_en_dash").unwrap();
+        assert!(floor.applied, "em dash floor should lift the score: {:?}", result.contributions);
+
+        let cfs = counterfactuals(&result);
+        let without_dash = cfs
+            .iter()
+            .find(|c| c.removed == "signal" && c.measurement == "em_en_dash")
+            .unwrap();
+        assert!(without_dash.score < result.score, "{cfs:?}");
+        assert_eq!(without_dash.description, format!("without em_en_dash this would be {}", without_dash.score));
+
+        let without_floor = cfs
+            .iter()
+            .find(|c| c.removed == "floor" && c.measurement == "em_en_dash")
+            .unwrap();
+        assert_eq!(without_floor.score, result.raw_score.round() as u8);
+    }
+
+    #[test]
+    fn test_lexicons_match_whole_words_and_count_occurrences() {
+        let text = "We shipped unlockable skins and a design update yesterday, nothing else.";
+        let result = analyze(text);
+        assert!(result.evidence.is_empty(), "{:?}", result.evidence);
+
+        let text = "Sign up today. Seriously, sign up now, and then sign up a friend too.";
+        let result = analyze(text);
+        let promo = result.contributions.iter().find(|c| c.measurement == "promotional").unwrap();
+        assert_eq!(promo.value, Some(3.0));
+        assert!(result.signals.iter().any(|s| s == "promotional_pattern"), "{:?}", result.signals);
+    }
+
+    #[test]
+    fn test_linkedin_template_structure() {
+        let text = "The 3 Habits That Changed My Career\n\n\
+                    I used to think talent was everything.\n\n\
+                    Then I watched average people outwork everyone around them.\n\n\
+                    🚀 Ship something every week\n\
+                    ✅ Track what actually matters\n\
+                    👉 Ask for feedback early\n\n\
+                    Which habit would you add?\n\n\
+                    #Leadership #Growth #CareerAdvice #Mindset";
+        let result = analyze(text);
+        for signal in ["title_case_hook", "emoji_bullets", "hook_body_cta", "hashtag_block"] {
+            assert!(result.signals.iter().any(|s| s == signal), "missing {signal}: {:?}", result.signals);
+        }
+        assert!(result.score >= 7, "Templated post scored too low: {} (signals: {:?})", result.score, result.signals);
+    }
+
+    #[test]
+    fn test_stylometry_signals() {
+        let text = "Organizations that systematically prioritize comprehensive documentation consistently \
+                    demonstrate measurably improved operational efficiency. Establishing standardized \
+                    communication frameworks facilitates collaboration across geographically distributed \
+                    teams. Leadership should evaluate implementation strategies carefully, considering \
+                    organizational readiness and available infrastructure. You should invest in your \
+                    team, your processes and your documentation today.";
+        let result = analyze(text);
+        for signal in ["dense_readability", "no_contractions", "reader_directed"] {
+            assert!(result.signals.iter().any(|s| s == signal), "missing {signal}: {:?}", result.signals);
+        }
+        let measured = |name: &str| result.contributions.iter().any(|c| c.measurement == name);
+        assert!(measured("flesch_kincaid") && measured("contraction_rate"));
+    }
+
+    #[test]
+    fn test_repetition_signals() {
+        let text = "The 1% take action while everyone else waits for permission. \
+                    The 1% think in decades while everyone else thinks in quarters. \
+                    The 1% never stop learning while everyone else stops at school. \
+                    Be the 1%.";
+        let result = analyze(text);
+        for signal in ["anaphora", "repeated_phrases"] {
+            assert!(result.signals.iter().any(|s| s == signal), "missing {signal}: {:?}", result.signals);
+        }
+        let openings = result.evidence.iter().filter(|e| e.signal == "anaphora").count();
+        assert_eq!(openings, 3);
+        assert!(result.evidence.iter().any(|e| e.signal == "repeated_phrases" && e.text == "while everyone else"));
+    }
+
+    #[test]
+    fn test_platform_profiles_adjust_scoring() {
+        let text = "Shipped the new onboarding flow today.\n\
+                    Took us three weeks longer than planned.\n\
+                    Worth it though, signups are already up.\n\
+                    Next up is the billing page.";
+        let on = |platform| super::analyze(text, &RULES, Some(&platform));
+
+        let twitter = on(Platform::Twitter);
+        let linkedin = on(Platform::LinkedIn);
+        assert!(!twitter.contributions.iter().any(|c| c.measurement == "linebreak_ratio"));
+        assert!(linkedin.signals.iter().any(|s| s == "line_per_sentence"), "{:?}", linkedin.signals);
+        assert!(linkedin.raw_score > twitter.raw_score, "linkedin {} vs twitter {}", linkedin.raw_score, twitter.raw_score);
+    }
+
+    #[test]
+    fn test_entities_are_cut_and_reported() {
+        let text = "Big\u{200B} news from @acme_ai today! Read more at https://acme.ai/launch.\n\n#AI #Startups #Launch";
+        let result = analyze(text);
+
+        let removed: Vec<(&str, &str)> = result.removed.iter().map(|r| (r.kind.as_str(), r.text.as_str())).collect();
+        assert_eq!(
+            removed,
+            [
+                ("mention", "@acme_ai"),
+                ("url", "https://acme.ai/launch"),
+                ("hashtag", "#AI"),
+                ("hashtag", "#Startups"),
+                ("hashtag", "#Launch"),
+            ]
+        );
+        assert_eq!(&text[result.removed[0].start..result.removed[0].end], "@acme_ai");
+        assert!(result.signals.iter().any(|s| s == "hashtag_block"), "{:?}", result.signals);
+        // "Big news from today! Read more at."
+        let words = result.contributions.iter().find(|c| c.measurement == "word_count").unwrap();
+        assert_eq!(words.value, Some(7.0));
+    }
+
+    #[test]
+    fn test_humanizer_tampering_is_undone_and_flagged() {
+        // Cyrillic е in "lеverage", zero-width spaces, a horizontal bar standing in for an em dash
+        let text = "We lеverage AI to move fast\u{2015}and it's a game\u{200B}changer. \
+                    Teams sеamlessly ship every\u{200B}single week now.";
+        let result = analyze(text);
+        for signal in ["homoglyph_substitution", "invisible_characters", "confusable_characters", "em_en_dash"] {
+            assert!(result.signals.iter().any(|s| s == signal), "missing {signal}: {:?}", result.signals);
+        }
+        assert!(result.score >= 8, "Tampered text scored too low: {}", result.score);
+
+        // Evidence found in the normalized text points at the original characters
+        let leverage = result.evidence.iter().find(|e| e.signal.contains("formulaic")).unwrap();
+        assert_eq!(leverage.text, "lеverage");
+        assert_eq!(&text[leverage.start..leverage.end], "lеverage");
+        let dash = result.evidence.iter().find(|e| e.signal == "em_en_dash").unwrap();
+        assert_eq!(dash.text, "\u{2015}");
+    }
+
+    #[test]
+    fn test_persian_joiners_are_not_tampering() {
+        let text = "امروز صبح می\u{200C}خواهم با بچه\u{200C}ها به بازار بروم و کمی میوه و نان تازه \
+                    بخرم، بعدش هم اگر حوصله داشتیم سری به خانه\u{200C}ی مادربزرگ بزنیم! هوا عالی است. \
+                    دوستانم هم می\u{200C}آیند؟ نمی\u{200C}دانم.";
+        let result = analyze(text);
+        assert!(!result.signals.iter().any(|s| s == "invisible_characters"), "{:?}", result.signals);
+        assert!(result.score <= 4, "Persian post scored too high: {} ({:?})", result.score, result.signals);
+    }
+
+    #[test]
+    fn test_minus_signs_are_not_confusables() {
+        let text = "Woke up to \u{2212}5°C this morning and the car wouldn't start. Dad says it \
+                    got down to \u{2212}12 back in '98 and we all survived, so I'm not complaining.";
+        let result = analyze(text);
+        assert!(!result.signals.iter().any(|s| s == "confusable_characters"), "{:?}", result.signals);
+        assert!(result.score <= 3, "Minus signs scored too high: {} ({:?})", result.score, result.signals);
+    }
+
+    #[test]
+    fn test_spanish_lexicons() {
+        let text = "En el mundo actual, es importante destacar que la inteligencia artificial \
+                    está transformando la manera en que trabajamos. Asimismo, las empresas deben \
+                    aprovechar estas herramientas con un enfoque holístico para potenciar su \
+                    crecimiento y adoptar las mejores prácticas del sector.";
+        let result = analyze(text);
+        assert_eq!(result.language.as_deref(), Some("es"));
+        assert!(result.language_supported);
+        assert!(result.signals.iter().any(|s| s == "formulaic_phrases"), "{:?}", result.signals);
+        assert!(result.score >= 6, "Spanish AI text scored too low: {} (signals: {:?})", result.score, result.signals);
+    }
+
+    #[test]
+    fn test_unsupported_language_uses_neutral_signals() {
+        let text = "Oggi ho finalmente finito di sistemare la cucina. Non è venuta perfetta, \
+                    ma sono molto contento del risultato. Mia moglie dice che le piastrelle sono \
+                    storte, ma secondo me così è ancora più interessante.";
+        let result = analyze(text);
+        assert_eq!(result.language.as_deref(), Some("it"));
+        assert!(!result.language_supported);
+        assert!(!result.signals.iter().any(|s| s.contains("formulaic") || s.contains("vocabulary")));
+    }
+
+    #[test]
+    fn test_cjk_post_gets_sentence_statistics() {
+        let text = "昨日は友達と山に登りました。天気が良くて、景色がとてもきれいでした！\
+                    頂上でおにぎりを食べて、少し昼寝をしました。帰りは雨が降ってきて大変だったけど、楽しかったです。";
+        let result = analyze(text);
+        assert!(!result.signals.iter().any(|s| s == "short_text_low_confidence"), "{:?}", result.signals);
+        assert_ne!(sentence_length_variance(&TextStats::new(text)), 50.0, "four CJK sentences should be measured");
+        assert!(type_token_ratio(&TextStats::new(text)) < 1.0);
+    }
 }
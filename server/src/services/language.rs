@@ -0,0 +1,55 @@
+use whatlang::Lang;
+
+/// Bytes of text looked at. Far more than a reliable guess needs, and keeps
+/// detection cheap on 50,000-char payloads.
+const SAMPLE_BYTES: usize = 4096;
+
+/// Detect the language of `text` as an ISO 639-1 code (ISO 639-3 for languages
+/// without one). Returns None when the detection is not reliable, which is
+/// common for very short posts.
+pub fn detect(text: &str) -> Option<String> {
+    let mut end = text.len().min(SAMPLE_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let info = whatlang::detect(&text[..end])?;
+    if !info.is_reliable() {
+        return None;
+    }
+    Some(iso639_1(info.lang()).unwrap_or_else(|| info.lang().code()).to_string())
+}
+
+fn iso639_1(lang: Lang) -> Option<&'static str> {
+    let code = match lang {
+        Lang::Eng => "en",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Deu => "de",
+        Lang::Fra => "fr",
+        Lang::Ita => "it",
+        Lang::Nld => "nl",
+        Lang::Pol => "pl",
+        Lang::Rus => "ru",
+        Lang::Ukr => "uk",
+        Lang::Tur => "tr",
+        Lang::Swe => "sv",
+        Lang::Dan => "da",
+        Lang::Nob => "nb",
+        Lang::Fin => "fi",
+        Lang::Ces => "cs",
+        Lang::Ron => "ro",
+        Lang::Hun => "hu",
+        Lang::Ell => "el",
+        Lang::Ara => "ar",
+        Lang::Heb => "he",
+        Lang::Hin => "hi",
+        Lang::Ind => "id",
+        Lang::Vie => "vi",
+        Lang::Tha => "th",
+        Lang::Jpn => "ja",
+        Lang::Kor => "ko",
+        Lang::Cmn => "zh",
+        _ => return None,
+    };
+    Some(code)
+}
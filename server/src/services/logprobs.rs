@@ -0,0 +1,241 @@
+//! Token log-probability detector over OpenAI-compatible completion servers.
+//!
+//! Instead of asking a model for a verdict and parsing its JSON, this detector
+//! asks a local server (vLLM, llama.cpp, ...) how likely the post's own tokens
+//! are: a legacy `/completions` request with `echo` and `logprobs` returns the
+//! log probability of every prompt token. From those it measures the token
+//! perplexity and, when a second "performer" model is configured, the
+//! Binoculars score (Hans et al., 2024): the observer's log perplexity divided
+//! by the observer/performer cross-perplexity. The servers must return top
+//! log probabilities, and both models must share a tokenizer.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::Endpoint;
+use crate::errors::AppError;
+use crate::services::ensemble::{self, Detector, Input, Verdict};
+
+/// Token perplexity and the Binoculars ratio, registered in `rules::MEASUREMENTS`.
+pub const MEASUREMENTS: &[&str] = &["token_perplexity", "binoculars"];
+
+/// Top log probabilities requested per token, for the cross-perplexity.
+const TOP_LOGPROBS: u32 = 20;
+
+/// Scored tokens needed before perplexity means anything.
+const MIN_TOKENS: usize = 10;
+
+#[derive(Serialize)]
+struct CompletionRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    /// One token is the least every server accepts; it is dropped.
+    max_tokens: u32,
+    temperature: f64,
+    echo: bool,
+    logprobs: u32,
+}
+
+#[derive(Deserialize)]
+struct CompletionResponse {
+    choices: Vec<Choice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    logprobs: Option<Logprobs>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    prompt_tokens: usize,
+}
+
+/// Per-token log probabilities (natural log) of the prompt and completion.
+#[derive(Debug, Default, Deserialize)]
+pub struct Logprobs {
+    pub tokens: Vec<String>,
+    /// None for the first token, which has no context.
+    pub token_logprobs: Vec<Option<f64>>,
+    #[serde(default)]
+    pub top_logprobs: Vec<Option<HashMap<String, f64>>>,
+}
+
+/// Log probabilities of the post's tokens under `endpoint`'s model.
+async fn prompt_logprobs(client: &Client, endpoint: &Endpoint, text: &str) -> Result<Logprobs, AppError> {
+    let request = CompletionRequest {
+        model: &endpoint.model,
+        prompt: text,
+        max_tokens: 1,
+        temperature: 0.0,
+        echo: true,
+        logprobs: TOP_LOGPROBS,
+    };
+    let url = format!("{}/completions", endpoint.url.trim_end_matches('/'));
+    let resp = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| AppError::LlmApi(format!("Logprobs request to {url} failed: {e}")))?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        return Err(AppError::LlmApi(format!("Logprobs server returned {status}: {body}")));
+    }
+    let body: CompletionResponse = resp
+        .json()
+        .await
+        .map_err(|e| AppError::LlmApi(format!("Failed to parse logprobs response: {e}")))?;
+
+    let mut logprobs = body
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|c| c.logprobs)
+        .ok_or_else(|| AppError::LlmApi(format!("{url} returned no logprobs; does it support `echo`?")))?;
+    // Keep the echoed prompt, drop the generated token
+    let prompt = body
+        .usage
+        .map_or(logprobs.tokens.len().saturating_sub(1), |u| u.prompt_tokens)
+        .min(logprobs.tokens.len());
+    logprobs.tokens.truncate(prompt);
+    logprobs.token_logprobs.truncate(prompt);
+    logprobs.top_logprobs.truncate(prompt);
+    Ok(logprobs)
+}
+
+/// exp of the mean negative log probability of the scored tokens.
+pub fn perplexity(observer: &Logprobs) -> Option<f64> {
+    let scored: Vec<f64> = observer.token_logprobs.iter().flatten().copied().collect();
+    (scored.len() >= MIN_TOKENS).then(|| (-scored.iter().sum::<f64>() / scored.len() as f64).exp())
+}
+
+/// Binoculars score: observer log perplexity over the cross-perplexity, the
+/// observer's expected surprise at the performer's next-token distribution.
+/// Both distributions are known only through their top log probabilities: the
+/// performer's is renormalized over its top tokens, and tokens missing from the
+/// observer's top list get its lowest listed log probability.
+pub fn binoculars(observer: &Logprobs, performer: &Logprobs) -> Result<Option<f64>, AppError> {
+    if observer.tokens != performer.tokens {
+        return Err(AppError::LlmApi(
+            "Observer and performer tokenize the post differently; Binoculars needs one tokenizer".to_string(),
+        ));
+    }
+    let (mut log_ppl, mut x_ppl, mut n) = (0.0, 0.0, 0usize);
+    for i in 0..observer.tokens.len() {
+        let (Some(logprob), Some(Some(observed)), Some(Some(performed))) = (
+            observer.token_logprobs[i],
+            observer.top_logprobs.get(i),
+            performer.top_logprobs.get(i),
+        ) else {
+            continue;
+        };
+        let floor = observed.values().copied().fold(f64::INFINITY, f64::min);
+        let total: f64 = performed.values().map(|lp| lp.exp()).sum();
+        if !floor.is_finite() || total <= 0.0 {
+            continue;
+        }
+        log_ppl -= logprob;
+        x_ppl -= performed
+            .iter()
+            .map(|(token, lp)| lp.exp() / total * observed.get(token).copied().unwrap_or(floor))
+            .sum::<f64>();
+        n += 1;
+    }
+    Ok((n >= MIN_TOKENS && x_ppl > 0.0).then(|| log_ppl / x_ppl))
+}
+
+/// Log probabilities from local model servers as an ensemble detector.
+pub struct LogProbs {
+    client: Client,
+    observer: Endpoint,
+    performer: Option<Endpoint>,
+}
+
+impl LogProbs {
+    pub fn new(client: &Client, observer: Endpoint, performer: Option<Endpoint>) -> Self {
+        Self {
+            client: client.clone(),
+            observer,
+            performer,
+        }
+    }
+}
+
+#[async_trait]
+impl Detector for LogProbs {
+    fn name(&self) -> &'static str {
+        "logprobs"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let text = input.stripped();
+        let observer = prompt_logprobs(&self.client, &self.observer, &text);
+        let (observer, performer) = match &self.performer {
+            Some(endpoint) => {
+                let (o, p) = futures::join!(observer, prompt_logprobs(&self.client, endpoint, &text));
+                (o?, Some(p?))
+            }
+            None => (observer.await?, None),
+        };
+
+        let mut measured = Vec::new();
+        if let Some(ppl) = perplexity(&observer) {
+            measured.push(("token_perplexity", ppl));
+        }
+        if let Some(performer) = &performer {
+            if let Some(score) = binoculars(&observer, performer)? {
+                measured.push(("binoculars", score));
+            }
+        }
+        let platform = Some(&input.platform);
+        let (score, fired) = ensemble::vote(&input.rules, platform, &measured);
+        Ok(Verdict {
+            score,
+            confidence: ensemble::CONFIDENCE,
+            signals: fired.into_iter().filter_map(|(_, tier)| tier.signal.clone()).collect(),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` tokens, each with log probability `logprob` and top list `top`.
+    fn logprobs(n: usize, logprob: f64, top: &[(&str, f64)]) -> Logprobs {
+        let top: HashMap<String, f64> = top.iter().map(|(t, lp)| (t.to_string(), *lp)).collect();
+        Logprobs {
+            tokens: (0..n).map(|i| format!("t{i}")).collect(),
+            token_logprobs: (0..n).map(|i| (i > 0).then_some(logprob)).collect(),
+            top_logprobs: (0..n).map(|i| (i > 0).then(|| top.clone())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_perplexity() {
+        let observer = logprobs(21, -2.0, &[]);
+        assert!((perplexity(&observer).unwrap() - 2f64.exp()).abs() < 1e-9);
+        assert!(perplexity(&logprobs(5, -2.0, &[])).is_none());
+    }
+
+    #[test]
+    fn test_binoculars() {
+        let observer = logprobs(21, -1.0, &[("a", -0.5), ("b", -2.0)]);
+        // Performer splits its mass evenly between "a" and "c" (unlisted for the observer)
+        let performer = logprobs(21, -1.0, &[("a", -0.7), ("c", -0.7)]);
+        // log ppl 1.0 over cross-entropy 0.5 * 0.5 + 0.5 * 2.0 = 1.25
+        let score = binoculars(&observer, &performer).unwrap().unwrap();
+        assert!((score - 0.8).abs() < 1e-9);
+
+        let mut other = logprobs(21, -1.0, &[]);
+        other.tokens[3] = "different".to_string();
+        assert!(binoculars(&observer, &other).is_err());
+    }
+}
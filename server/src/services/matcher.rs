@@ -0,0 +1,168 @@
+//! Compiled multi-pattern lexicon matcher.
+//!
+//! Each lexicon is compiled once, when the rules load, into a single
+//! Aho-Corasick automaton. Entries match whole words only, so "unlock" no
+//! longer fires on "unlocked" and "sign up" no longer fires on "design update".
+//! Inflections are opted into per entry:
+//!
+//! - `{a,b,...}` expands to each alternative; an empty one keeps the bare form
+//!   (`unlock{,s,ed}` matches unlock, unlocks and unlocked)
+//! - a trailing `*` matches any word ending (`harness*` matches harnesses)
+//!
+//! Straight apostrophes also match typographic ones (`it's` matches `it’s`).
+//! Every occurrence is reported, not just whether an entry is present.
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use std::ops::Range;
+
+use crate::services::text;
+
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    automaton: AhoCorasick,
+    /// Expanded pattern index -> (entry index, matches any word ending).
+    patterns: Vec<(usize, bool)>,
+}
+
+impl Matcher {
+    pub fn new(entries: &[String]) -> Result<Self, String> {
+        let mut expanded = Vec::new();
+        let mut patterns = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            let (body, stem) = match entry.strip_suffix('*') {
+                Some(body) => (body, true),
+                None => (entry.as_str(), false),
+            };
+            for form in expand(body).map_err(|e| format!("Entry {entry:?}: {e}"))? {
+                if form.trim().is_empty() {
+                    return Err(format!("Entry {entry:?} expands to an empty pattern"));
+                }
+                if form.contains('\'') {
+                    expanded.push(form.replace('\'', "’"));
+                    patterns.push((index, stem));
+                }
+                expanded.push(form);
+                patterns.push((index, stem));
+            }
+        }
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(&expanded)
+            .map_err(|e| e.to_string())?;
+        Ok(Self { automaton, patterns })
+    }
+
+    /// Every whole-word occurrence in `haystack` (which must be lowercase) as
+    /// (entry index, byte span). Overlapping candidates resolve to the leftmost,
+    /// then longest, match.
+    pub fn find(&self, haystack: &str) -> Vec<(usize, Range<usize>)> {
+        let mut candidates: Vec<(usize, Range<usize>)> = self
+            .automaton
+            .find_overlapping_iter(haystack)
+            .filter_map(|m| {
+                let (entry, stem) = self.patterns[m.pattern().as_usize()];
+                let mut end = m.end();
+                if stem {
+                    end += haystack[end..]
+                        .find(|c: char| !text::is_word_char(c))
+                        .unwrap_or(haystack.len() - end);
+                }
+                on_word_boundary(haystack, m.start(), end).then_some((entry, m.start()..end))
+            })
+            .collect();
+
+        candidates.sort_by_key(|(_, span)| (span.start, std::cmp::Reverse(span.end)));
+        let mut taken_until = 0;
+        candidates.retain(|(_, span)| {
+            let keep = span.start >= taken_until;
+            if keep {
+                taken_until = span.end;
+            }
+            keep
+        });
+        candidates
+    }
+}
+
+/// Whether `haystack[start..end]` doesn't run into a neighbouring word.
+fn on_word_boundary(haystack: &str, start: usize, end: usize) -> bool {
+    let inner_first = haystack[start..end].chars().next();
+    let inner_last = haystack[start..end].chars().next_back();
+    let before = haystack[..start].chars().next_back();
+    let after = haystack[end..].chars().next();
+
+    let touches = |inner: Option<char>, outer: Option<char>| {
+        inner.is_some_and(text::is_word_char) && outer.is_some_and(text::is_word_char)
+    };
+    !touches(inner_first, before) && !touches(inner_last, after)
+}
+
+/// Expand `{a,b}` groups into every combination.
+fn expand(pattern: &str) -> Result<Vec<String>, String> {
+    let Some(open) = pattern.find('{') else {
+        if pattern.contains('}') {
+            return Err("unmatched `}`".to_string());
+        }
+        return Ok(vec![pattern.to_string()]);
+    };
+    let close = pattern[open..]
+        .find('}')
+        .map(|i| open + i)
+        .ok_or("unmatched `{`")?;
+    let (head, alternatives, tail) = (&pattern[..open], &pattern[open + 1..close], &pattern[close + 1..]);
+    if alternatives.contains('{') {
+        return Err("nested `{`".to_string());
+    }
+
+    let tails = expand(tail)?;
+    Ok(alternatives
+        .split(',')
+        .flat_map(|alt| tails.iter().map(move |t| format!("{head}{alt}{t}")))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched<'a>(entries: &[&str], text: &'a str) -> Vec<&'a str> {
+        let entries: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+        Matcher::new(&entries)
+            .unwrap()
+            .find(text)
+            .into_iter()
+            .map(|(_, span)| &text[span])
+            .collect()
+    }
+
+    #[test]
+    fn test_whole_words_only() {
+        assert!(matched(&["unlock", "harness"], "we unlocked harnesses").is_empty());
+        assert!(matched(&["sign up"], "a design update").is_empty());
+        assert_eq!(matched(&["sign up"], "sign up, then sign up again"), ["sign up", "sign up"]);
+    }
+
+    #[test]
+    fn test_inflections() {
+        assert_eq!(
+            matched(&["unlock{,s,ed}", "harness*"], "unlock what harnesses unlocked, not unlocking"),
+            ["unlock", "harnesses", "unlocked"]
+        );
+        assert_eq!(matched(&["div{e,ing} {in,}to"], "diving into, dive to"), ["diving into", "dive to"]);
+        assert!(Matcher::new(&["bad{".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_overlaps_prefer_longest() {
+        let entries = ["here are", "here are the"];
+        assert_eq!(matched(&entries, "here are the tips"), ["here are the"]);
+    }
+
+    #[test]
+    fn test_symbols_and_apostrophes() {
+        assert_eq!(matched(&["top 1%"], "the top 1%!"), ["top 1%"]);
+        assert_eq!(matched(&["it's worth noting"], "it’s worth noting"), ["it’s worth noting"]);
+        assert_eq!(matched(&["人工知能"], "これは人工知能です"), ["人工知能"]);
+    }
+}
@@ -1,4 +1,28 @@
 pub mod anthropic;
+pub mod baseline;
+pub mod campaigns;
+pub mod classifier;
 pub mod detector;
+pub mod embeddings;
+pub mod ensemble;
+pub mod entities;
 pub mod heuristics;
+pub mod language;
+pub mod logprobs;
+pub mod matcher;
 pub mod openrouter;
+pub mod perplexity;
+pub mod repetition;
+pub mod rules;
+pub mod segmenter;
+pub mod short_text;
+pub mod stats;
+pub mod structure;
+pub mod stylometry;
+pub mod tampering;
+pub mod templates;
+pub mod text;
+#[cfg(feature = "transformer")]
+pub mod transformer;
+#[cfg(feature = "watermark")]
+pub mod watermark;
@@ -1,9 +1,38 @@
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
 use crate::errors::AppError;
-use crate::services::detector::{LlmResult, SYSTEM_PROMPT, parse_score};
+use crate::services::detector::{LlmResult, parse_score};
+use crate::services::ensemble::{Detector, Input, Verdict};
+
+/// The OpenRouter chat completions as an ensemble detector.
+pub struct OpenRouter {
+    client: Client,
+    config: Config,
+}
+
+impl OpenRouter {
+    pub fn new(client: &Client, config: &Config) -> Self {
+        Self {
+            client: client.clone(),
+            config: config.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Detector for OpenRouter {
+    fn name(&self) -> &'static str {
+        "openrouter"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let llm = analyze(&self.client, &self.config, &input.system_prompt(), &input.stripped()).await?;
+        Ok(llm.into())
+    }
+}
 
 #[derive(Serialize)]
 struct ChatRequest {
@@ -34,7 +63,7 @@ struct ResponseMessage {
     content: String,
 }
 
-pub async fn analyze(client: &Client, config: &Config, text: &str) -> Result<LlmResult, AppError> {
+pub async fn analyze(client: &Client, config: &Config, system_prompt: &str, text: &str) -> Result<LlmResult, AppError> {
     if config.openrouter_api_key.is_empty() {
         return Err(AppError::LlmApi("OPENROUTER_API_KEY not configured".to_string()));
     }
@@ -44,7 +73,7 @@ pub async fn analyze(client: &Client, config: &Config, text: &str) -> Result<Llm
         messages: vec![
             Message {
                 role: "system".to_string(),
-                content: SYSTEM_PROMPT.to_string(),
+                content: system_prompt.to_string(),
             },
             Message {
                 role: "user".to_string(),
@@ -0,0 +1,369 @@
+//! N-gram language model perplexity detector.
+//!
+//! Language models write the words a language model expects, so AI text is
+//! unusually predictable (low perplexity) and evenly so from sentence to
+//! sentence (low perplexity burstiness), while people mix predictable
+//! sentences with odd ones. The model is an ARPA file (as written by KenLM's
+//! `lmplz` or SRILM) that the operator supplies through
+//! `PERPLEXITY_MODEL_PATH`; scoring runs locally with Katz backoff. Posts are
+//! scored on the normalized, entity-free text and the sentences of the
+//! heuristic analysis, and words are lowercased, so the model should be
+//! trained on lowercased words.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::errors::AppError;
+use crate::models::{Evidence, Platform};
+use crate::services::ensemble::{self, Detector, Input, Verdict};
+use crate::services::rules::Rules;
+use crate::services::tampering::Normalized;
+use crate::services::text;
+
+/// Per-word perplexity and its spread across sentences, registered in `rules::MEASUREMENTS`.
+pub const MEASUREMENTS: &[&str] = &["perplexity", "perplexity_burstiness"];
+
+/// log10 probability of a word missing from a model without `<unk>`.
+const UNKNOWN_LOGPROB: f64 = -7.0;
+
+/// Sentences needed before perplexity burstiness means anything.
+const MIN_SENTENCES: usize = 3;
+
+/// An ARPA back-off language model.
+#[derive(Debug)]
+pub struct LanguageModel {
+    order: usize,
+    vocabulary: HashMap<String, u32>,
+    /// Word ids, oldest first -> (log10 probability, log10 backoff).
+    ngrams: HashMap<Vec<u32>, (f32, f32)>,
+    unknown: Option<u32>,
+}
+
+/// Log probabilities of a post under the model.
+#[derive(Debug)]
+pub struct Scored {
+    /// Every word with its byte span in the original text and log10 probability.
+    pub tokens: Vec<(Range<usize>, f64)>,
+    /// Every sentence with its byte span in the original text and perplexity.
+    pub sentences: Vec<(Range<usize>, f64)>,
+    /// Perplexity over all words and sentence ends.
+    pub perplexity: f64,
+    /// Coefficient of variation of the sentence perplexities; None below `MIN_SENTENCES`.
+    pub burstiness: Option<f64>,
+}
+
+impl LanguageModel {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        Self::parse(&data)
+    }
+
+    /// Parse an ARPA file: a `\data\` header of n-gram counts, one `\N-grams:`
+    /// section per order with `logprob w1 .. wN [backoff]` lines, then `\end\`.
+    pub fn parse(data: &str) -> Result<Self, String> {
+        let mut model = Self {
+            order: 0,
+            vocabulary: HashMap::new(),
+            ngrams: HashMap::new(),
+            unknown: None,
+        };
+        let mut counts: Vec<usize> = Vec::new();
+        let mut section: Option<usize> = None;
+        let mut ended = false;
+
+        for (i, line) in data.lines().enumerate() {
+            let line = line.trim();
+            let at = || format!("ARPA line {}", i + 1);
+            if line.is_empty() {
+                continue;
+            }
+            if line == "\\data\\" {
+                section = Some(0);
+            } else if line == "\\end\\" {
+                ended = true;
+                break;
+            } else if let Some(n) = line.strip_prefix('\\').and_then(|l| l.strip_suffix("-grams:")) {
+                let n: usize = n.parse().map_err(|_| format!("{}: bad section `{line}`", at()))?;
+                if n == 0 || n > counts.len() {
+                    return Err(format!("{}: `{line}` is not declared in \\data\\", at()));
+                }
+                section = Some(n);
+            } else if let Some(count) = line.strip_prefix("ngram ") {
+                let (n, count) = count.split_once('=').ok_or_else(|| format!("{}: bad count `{line}`", at()))?;
+                let (n, count): (usize, usize) = match (n.trim().parse(), count.trim().parse()) {
+                    (Ok(n), Ok(count)) => (n, count),
+                    _ => return Err(format!("{}: bad count `{line}`", at())),
+                };
+                if n != counts.len() + 1 {
+                    return Err(format!("{}: n-gram counts must be listed in order", at()));
+                }
+                counts.push(count);
+            } else {
+                let n = match section {
+                    Some(n) if n > 0 => n,
+                    _ => return Err(format!("{}: n-gram outside a `\\N-grams:` section", at())),
+                };
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() != n + 1 && fields.len() != n + 2 {
+                    return Err(format!("{}: expected {n} words", at()));
+                }
+                let number = |s: &str| s.parse::<f32>().map_err(|_| format!("{}: bad number `{s}`", at()));
+                let logprob = number(fields[0])?;
+                let backoff = if fields.len() == n + 2 { number(fields[n + 1])? } else { 0.0 };
+                let ids = fields[1..=n].iter().map(|w| model.intern(w)).collect();
+                model.ngrams.insert(ids, (logprob, backoff));
+            }
+        }
+
+        if !ended {
+            return Err("ARPA file has no `\\end\\` marker".to_string());
+        }
+        if counts.is_empty() {
+            return Err("ARPA file declares no n-grams".to_string());
+        }
+        model.order = counts.len();
+        model.unknown = model.vocabulary.get("<unk>").copied();
+        Ok(model)
+    }
+
+    fn intern(&mut self, word: &str) -> u32 {
+        let next = self.vocabulary.len() as u32;
+        *self.vocabulary.entry(word.to_string()).or_insert(next)
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// log10 probability of the last word of `ngram` given the words before it,
+    /// backing off to shorter histories.
+    fn logprob(&self, ngram: &[Option<u32>]) -> f64 {
+        let word = ngram.last().copied().flatten().or(self.unknown);
+        let Some(word) = word else {
+            return UNKNOWN_LOGPROB;
+        };
+        // Longest known n-gram ending in `word`, plus the backoffs of the longer histories
+        let mut backoff = 0.0;
+        for start in 0..ngram.len() {
+            let history = &ngram[start..ngram.len() - 1];
+            let Some(mut key) = history.iter().copied().collect::<Option<Vec<u32>>>() else {
+                continue;
+            };
+            key.push(word);
+            if let Some((logprob, _)) = self.ngrams.get(&key) {
+                return backoff + *logprob as f64;
+            }
+            key.pop();
+            if let Some((_, history_backoff)) = self.ngrams.get(&key) {
+                backoff += *history_backoff as f64;
+            }
+        }
+        backoff + UNKNOWN_LOGPROB
+    }
+
+    /// Per-word log probabilities of `content`, over the sentences at byte
+    /// ranges `spans` of its text, with each sentence wrapped in `<s>` .. `</s>`.
+    pub fn score(&self, content: &Normalized, spans: &[Range<usize>]) -> Scored {
+        let start = self.vocabulary.get("<s>").copied();
+        let end = self.vocabulary.get("</s>").copied();
+
+        let mut tokens = Vec::new();
+        let mut sentences = Vec::new();
+        let (mut total, mut count) = (0.0, 0usize);
+        for span in spans {
+            let words = text::word_indices(&content.text[span.clone()]);
+            if words.is_empty() {
+                continue;
+            }
+            let mut context = vec![start];
+            let mut sum = 0.0;
+            for (offset, word) in &words {
+                context.push(self.vocabulary.get(&word.to_lowercase()).copied().or(self.unknown));
+                let logprob = self.logprob(window(&context, self.order));
+                let at = span.start + offset;
+                tokens.push((content.span(at..at + word.len()), logprob));
+                sum += logprob;
+            }
+            context.push(end);
+            sum += self.logprob(window(&context, self.order));
+
+            let n = words.len() + 1;
+            sentences.push((content.span(span.clone()), perplexity(sum, n)));
+            total += sum;
+            count += n;
+        }
+
+        let burstiness = (sentences.len() >= MIN_SENTENCES).then(|| {
+            let values: Vec<f64> = sentences.iter().map(|(_, p)| *p).collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance = values.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / values.len() as f64;
+            variance.sqrt() / mean
+        });
+        Scored {
+            tokens,
+            sentences,
+            perplexity: if count > 0 { perplexity(total, count) } else { 0.0 },
+            burstiness,
+        }
+    }
+}
+
+/// The last `order` entries of `context`.
+fn window(context: &[Option<u32>], order: usize) -> &[Option<u32>] {
+    &context[context.len().saturating_sub(order)..]
+}
+
+fn perplexity(log10_sum: f64, count: usize) -> f64 {
+    10f64.powf(-log10_sum / count as f64)
+}
+
+/// Prior-weighted vote over the perplexity measurements, with the sentences
+/// matching a fired tier as its evidence.
+pub fn judge(scored: &Scored, content: &str, rules: &Rules, platform: Option<&Platform>) -> Verdict {
+    let mut measured = Vec::new();
+    if !scored.tokens.is_empty() {
+        measured.push(("perplexity", scored.perplexity));
+    }
+    if let Some(burstiness) = scored.burstiness {
+        measured.push(("perplexity_burstiness", burstiness));
+    }
+    let (score, fired) = ensemble::vote(rules, platform, &measured);
+
+    let mut signals = Vec::new();
+    let mut evidence = Vec::new();
+    for (name, tier) in fired {
+        let Some(signal) = &tier.signal else { continue };
+        signals.push(signal.clone());
+        if name == "perplexity" {
+            evidence.extend(
+                scored
+                    .sentences
+                    .iter()
+                    .filter(|(_, p)| tier.matches(*p))
+                    .map(|(span, _)| Evidence::new(signal, content, span.clone(), content[..span.start].chars().count())),
+            );
+        }
+    }
+    Verdict {
+        score,
+        confidence: ensemble::CONFIDENCE,
+        signals,
+        evidence,
+        ..Default::default()
+    }
+}
+
+/// An n-gram language model as an ensemble detector.
+pub struct Perplexity {
+    model: Arc<LanguageModel>,
+}
+
+impl Perplexity {
+    pub fn new(model: LanguageModel) -> Self {
+        Self { model: Arc::new(model) }
+    }
+}
+
+#[async_trait]
+impl Detector for Perplexity {
+    fn name(&self) -> &'static str {
+        "perplexity"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let analysis = input.analysis().await?;
+        let content = input.content.clone();
+        let platform = input.platform.clone();
+        let rules = input.rules.clone();
+        let model = self.model.clone();
+        tokio::task::spawn_blocking(move || {
+            let scored = model.score(&analysis.content, &analysis.sentences);
+            judge(&scored, &content, &rules, Some(&platform))
+        })
+            .await
+            .map_err(|e| AppError::Internal(format!("Perplexity scoring panicked: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::heuristics::{Analysis, Context};
+
+    /// Bigram model over "the cat sat" with an `<unk>` entry.
+    const ARPA: &str = "\\data\\
+ngram 1=6
+ngram 2=4
+
+\\1-grams:
+-1.0\t<unk>\t0.0
+-99\t<s>\t-0.5
+-1.0\t</s>\t0.0
+-0.5\tthe\t-0.3
+-1.0\tcat\t-0.2
+-1.0\tsat\t-0.1
+
+\\2-grams:
+-0.1\t<s> the
+-0.2\tthe cat
+-0.1\tcat sat
+-0.1\tsat </s>
+
+\\end\\
+";
+
+    /// `text` scored on the sentences of its heuristic analysis.
+    fn score(model: &LanguageModel, text: &str) -> Scored {
+        let analysis = Analysis::new(text, &Rules::builtin(), None, &Context::default());
+        model.score(&analysis.content, &analysis.sentences)
+    }
+
+    #[test]
+    fn test_backoff_probabilities() {
+        let model = LanguageModel::parse(ARPA).unwrap();
+        assert_eq!(model.order(), 2);
+        let id = |w: &str| model.vocabulary.get(w).copied();
+
+        // Seen bigram
+        assert!((model.logprob(&[id("the"), id("cat")]) - -0.2).abs() < 1e-6);
+        // Unseen bigram: backoff(cat) + p(the)
+        assert!((model.logprob(&[id("cat"), id("the")]) - (-0.2 + -0.5)).abs() < 1e-6);
+        // Unknown word: backoff(the) + p(<unk>)
+        assert!((model.logprob(&[id("the"), None]) - (-0.3 + -1.0)).abs() < 1e-6);
+
+        let scored = score(&model, "The cat sat.");
+        assert_eq!(scored.tokens.len(), 3);
+        assert_eq!(scored.tokens[0].0, 0..3);
+        // log10 sum -0.5 over 4 events (3 words + </s>)
+        assert!((scored.perplexity - 10f64.powf(0.5 / 4.0)).abs() < 1e-6);
+        assert!(scored.burstiness.is_none());
+
+        let scored = score(&model, "The cat sat. Cat the dog. The cat sat. https://example.com");
+        assert_eq!(scored.sentences.len(), 3);
+        assert!(scored.sentences[1].1 > scored.sentences[0].1);
+        assert!(scored.burstiness.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_invalid_arpa_rejected() {
+        assert!(LanguageModel::parse("\\data\\\nngram 1=1\n\n\\1-grams:\n-1.0\tthe\n").is_err());
+        assert!(LanguageModel::parse("\\data\\\nngram 1=1\n\\2-grams:\n-1.0\ta b\n\\end\\\n").is_err());
+        assert!(LanguageModel::parse("\\data\\\nngram 1=1\n\\1-grams:\nx\tthe\n\\end\\\n").is_err());
+    }
+
+    #[test]
+    fn test_low_perplexity_votes_ai() {
+        let model = LanguageModel::parse(ARPA).unwrap();
+        let content = "The cat sat. The cat sat. The cat sat.";
+        let verdict = judge(&score(&model, content), content, &Rules::builtin(), None);
+        assert!(verdict.signals.contains(&"low_perplexity".to_string()));
+        assert!(verdict.signals.contains(&"flat_perplexity".to_string()));
+        assert_eq!(verdict.evidence.len(), 3);
+        assert_eq!(verdict.evidence[1].text, "The cat sat.");
+        assert!(verdict.score >= 6);
+    }
+}
@@ -0,0 +1,224 @@
+//! Repetition inside one post: repeated phrases, parallel sentence openings
+//! (anaphora) and sentence-frame templates.
+//!
+//! AI threads hammer one frame ("The 1% take action. The 1% think long term.
+//! The 1% never quit.") and echo their key phrases. Type-token ratio only sees
+//! that as slightly lower diversity; this module finds the repeats themselves.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::services::stats::TextStats;
+
+/// Length of the word n-grams checked for repetition.
+const NGRAM: usize = 3;
+
+/// Below this many words, repeated n-grams are noise.
+const MIN_WORDS: usize = 20;
+
+/// Opening words compared for anaphora.
+const OPENING: usize = 2;
+
+/// Shortest sentence (in words) that can share a frame.
+const MIN_FRAME_WORDS: usize = 4;
+
+#[derive(Debug, Default)]
+pub struct Repetition {
+    /// Share of words inside an n-gram that occurs more than once.
+    pub ngram_repetition: f64,
+    /// Every occurrence of a repeated n-gram, overlapping ones merged.
+    pub repeated_phrases: Vec<Range<usize>>,
+    /// Size of the largest group of sentences opening with the same words.
+    pub anaphora: usize,
+    /// Opening words of that group's sentences.
+    pub anaphora_openings: Vec<Range<usize>>,
+    /// Sentences sharing a frame with another sentence.
+    pub frames: Vec<Range<usize>>,
+}
+
+/// Find repetition in `stats.text`. Spans are byte ranges into it.
+pub fn analyze(stats: &TextStats) -> Repetition {
+    let words: Vec<&str> = stats.lower_words().map(|(_, w)| w).collect();
+    let sentences = sentence_words(stats);
+
+    let (ngram_repetition, repeated_phrases) = repeated_ngrams(stats, &words);
+    let anaphora_openings = anaphora(stats, &words, &sentences);
+    Repetition {
+        ngram_repetition,
+        repeated_phrases,
+        anaphora: anaphora_openings.len(),
+        anaphora_openings,
+        frames: frames(stats, &words, &sentences),
+    }
+}
+
+/// Word index range of each prose sentence. List items are left out: parallel
+/// bullets are list structure, which the structure signals already measure.
+fn sentence_words(stats: &TextStats) -> Vec<Range<usize>> {
+    let starts: Vec<usize> = stats.words.iter().map(|(i, _)| *i).collect();
+    stats
+        .sentences
+        .iter()
+        .filter(|s| !s.list_item)
+        .map(|s| {
+            let from = starts.partition_point(|&i| i < s.start);
+            let to = starts.partition_point(|&i| i < s.start + s.text.len());
+            from..to
+        })
+        .collect()
+}
+
+/// Byte span in `stats.text` of words `from..to`.
+fn word_span(stats: &TextStats, words: Range<usize>) -> Range<usize> {
+    let (start, _) = stats.words[words.start];
+    let (last, word) = stats.words[words.end - 1];
+    start..last + word.len()
+}
+
+/// Share of words covered by a repeated n-gram, and the merged spans of every
+/// occurrence. N-grams made only of short words ("it is a") repeat in any prose
+/// and are skipped.
+fn repeated_ngrams(stats: &TextStats, words: &[&str]) -> (f64, Vec<Range<usize>>) {
+    if words.len() < MIN_WORDS {
+        return (0.0, Vec::new());
+    }
+
+    let mut seen: HashMap<&[&str], Vec<usize>> = HashMap::new();
+    for (i, gram) in words.windows(NGRAM).enumerate() {
+        if gram.iter().any(|w| w.chars().count() >= 4) {
+            seen.entry(gram).or_default().push(i);
+        }
+    }
+
+    let mut covered = vec![false; words.len()];
+    for positions in seen.values().filter(|p| p.len() > 1) {
+        for &i in positions {
+            covered[i..i + NGRAM].fill(true);
+        }
+    }
+
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < covered.len() {
+        if !covered[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < covered.len() && covered[i] {
+            i += 1;
+        }
+        spans.push(word_span(stats, start..i));
+    }
+
+    let share = covered.iter().filter(|&&c| c).count() as f64 / words.len() as f64;
+    (share, spans)
+}
+
+/// Opening-word spans of the largest group of sentences that start with the
+/// same words. Empty when no two sentences share an opening.
+fn anaphora(stats: &TextStats, words: &[&str], sentences: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut groups: HashMap<&[&str], Vec<Range<usize>>> = HashMap::new();
+    for range in sentences.iter().filter(|r| r.len() > OPENING) {
+        let opening = range.start..range.start + OPENING;
+        groups
+            .entry(&words[opening.clone()])
+            .or_default()
+            .push(word_span(stats, opening));
+    }
+
+    // Ties go to the earliest group, so the result doesn't depend on hash order
+    groups
+        .into_values()
+        .filter(|g| g.len() > 1)
+        .max_by_key(|g| (g.len(), std::cmp::Reverse(g[0].start)))
+        .unwrap_or_default()
+}
+
+/// Spans of sentences that share a frame with another sentence: the same number
+/// of words, matching in at least half of the positions, with the slots in
+/// between filled differently ("Success is not luck. Success is not talent.").
+/// Sentences sharing only their opening words are anaphora, not a frame, so at
+/// least one match must come after the opening.
+fn frames(stats: &TextStats, words: &[&str], sentences: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut by_length: HashMap<usize, Vec<&Range<usize>>> = HashMap::new();
+    for range in sentences.iter().filter(|r| r.len() >= MIN_FRAME_WORDS) {
+        by_length.entry(range.len()).or_default().push(range);
+    }
+
+    let mut framed: Vec<Range<usize>> = Vec::new();
+    for group in by_length.values() {
+        let mut in_frame = vec![false; group.len()];
+        for a in 0..group.len() {
+            for b in a + 1..group.len() {
+                if share_frame(&words[group[a].clone()], &words[group[b].clone()]) {
+                    in_frame[a] = true;
+                    in_frame[b] = true;
+                }
+            }
+        }
+        framed.extend(
+            group
+                .iter()
+                .zip(in_frame)
+                .filter(|(_, f)| *f)
+                .map(|(range, _)| word_span(stats, (*range).clone())),
+        );
+    }
+    framed.sort_by_key(|span| span.start);
+    framed
+}
+
+fn share_frame(a: &[&str], b: &[&str]) -> bool {
+    let same: Vec<bool> = a.iter().zip(b).map(|(x, y)| x == y).collect();
+    let matches = same.iter().filter(|&&s| s).count();
+    matches * 2 >= a.len() && same[OPENING..].iter().any(|&s| s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matched<'a>(text: &'a str, spans: &[Range<usize>]) -> Vec<&'a str> {
+        spans.iter().map(|s| &text[s.clone()]).collect()
+    }
+
+    #[test]
+    fn test_repeated_phrases_and_anaphora() {
+        let text = "The 1% take action while everyone else waits for permission. \
+                    The 1% think in decades instead of quarters. \
+                    The 1% never stop learning from their mistakes. \
+                    Most people never stop learning about excuses instead.";
+        let r = analyze(&TextStats::new(text));
+
+        assert_eq!(r.anaphora, 3);
+        assert_eq!(matched(text, &r.anaphora_openings), ["The 1", "The 1", "The 1"]);
+        assert!(matched(text, &r.repeated_phrases).contains(&"never stop learning"), "{r:?}");
+        assert!(r.ngram_repetition > 0.1, "{r:?}");
+    }
+
+    #[test]
+    fn test_sentence_frames() {
+        let text = "Success is not about luck at all. Failure is not about talent at all. \
+                    My dog ate the neighbour's sandwich yesterday afternoon.";
+        let r = analyze(&TextStats::new(text));
+
+        assert_eq!(
+            matched(text, &r.frames),
+            ["Success is not about luck at all", "Failure is not about talent at all"]
+        );
+        assert_eq!(r.anaphora, 0);
+    }
+
+    #[test]
+    fn test_varied_text_has_no_repetition() {
+        let text = "Went hiking with my sister on Saturday. The trail was muddier than we expected, \
+                    and she slipped twice near the creek. We still made it to the top before \
+                    lunch. Tomorrow I'm sleeping in, my legs are wrecked.";
+        let r = analyze(&TextStats::new(text));
+
+        assert!(r.repeated_phrases.is_empty() && r.frames.is_empty(), "{r:?}");
+        assert_eq!(r.anaphora, 0);
+        assert_eq!(r.ngram_repetition, 0.0);
+    }
+}
@@ -0,0 +1,661 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use crate::models::Platform;
+use crate::services::ensemble::DETECTORS;
+use crate::services::heuristics::{self, LEXICONS};
+use crate::services::matcher::Matcher;
+use crate::services::{baseline, logprobs, perplexity, templates};
+
+/// Built-in rules, embedded at compile time. A user rules file is layered over these.
+const BUILTIN_RULES: &str = include_str!("../../rules/default.toml");
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    pub version: String,
+    pub language: LanguageRules,
+    pub short_text: ShortTextRules,
+    pub watermark: WatermarkRules,
+    pub author_baseline: AuthorBaselineRules,
+    pub templates: TemplateRules,
+    pub campaigns: CampaignRules,
+    pub neighbours: NeighbourRules,
+    pub prior: Vote,
+    /// Language code -> lexicon name -> entries.
+    pub lexicons: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    pub signals: BTreeMap<String, Vec<Tier>>,
+    #[serde(default)]
+    pub floors: Vec<Floor>,
+    pub labels: Labels,
+    /// How detector scores are combined into the final score.
+    pub ensemble: EnsembleRules,
+    /// Platform name -> scoring profile layered over the global signals.
+    #[serde(default)]
+    pub platforms: BTreeMap<String, Profile>,
+    /// `lexicons`, compiled. Built by `parse`.
+    #[serde(skip)]
+    matchers: BTreeMap<String, BTreeMap<String, Matcher>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageRules {
+    /// Lexicons used when the language can't be detected reliably.
+    pub default: String,
+    /// Confidence multiplier for posts in a language without lexicons.
+    pub unsupported_confidence: f64,
+}
+
+/// Posts too short for text statistics, scored on short-text features instead.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShortTextRules {
+    /// Posts with fewer words take the short-text path.
+    pub below: usize,
+    /// Confidence multiplier for short posts.
+    pub confidence: f64,
+}
+
+/// When a watermark z-test counts as a hit.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatermarkRules {
+    /// Lowest z-score that counts as watermarked.
+    pub z_threshold: f64,
+    /// Fewer tested tokens never count as watermarked.
+    pub min_tokens: usize,
+}
+
+/// Comparing a post with its author's history on the same platform.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthorBaselineRules {
+    /// Past posts needed before the author's baseline is used.
+    pub min_posts: usize,
+    /// Most recent posts the baseline is built from.
+    pub max_posts: usize,
+    /// Share of the author's posts reaching a floor that makes it a habit.
+    pub habitual: f64,
+    /// Floors lifted for authors who habitually reach them.
+    #[serde(default)]
+    pub waive_floors: Vec<String>,
+}
+
+/// When two stored posts count as the same template.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateRules {
+    /// Lowest estimated share of word 3-shingles two near-duplicates have in common.
+    pub min_similarity: f64,
+    /// Days of stored posts `/api/templates` clusters.
+    pub lookback_days: i64,
+}
+
+/// Nearest labeled reference examples (embeddings detector).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NeighbourRules {
+    /// Examples retrieved per post.
+    pub k: usize,
+    /// Examples less cosine-similar than this are not neighbours.
+    pub min_similarity: f64,
+}
+
+/// Grouping authors who post alike AI-flagged posts close together in time.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CampaignRules {
+    /// Stored posts scoring at least this take part.
+    pub min_score: i32,
+    /// Most minutes between two linked posts.
+    pub window_minutes: i64,
+    /// Days of history each clustering run looks at.
+    pub lookback_days: i64,
+    /// Signals both posts need before their signal sets can link them.
+    pub min_signals: usize,
+    /// Lowest Jaccard overlap of two posts' signal sets that links them.
+    pub min_signal_overlap: f64,
+    /// Fewest authors in a campaign.
+    pub min_authors: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Vote {
+    pub score: f64,
+    pub weight: f64,
+}
+
+/// One threshold band of a signal. All bounds that are set must match.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tier {
+    pub signal: Option<String>,
+    pub below: Option<f64>,
+    pub above: Option<f64>,
+    pub at_least: Option<f64>,
+    pub score: f64,
+    pub weight: f64,
+}
+
+/// Minimum heuristic score once a measurement reaches a threshold.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Floor {
+    pub measurement: String,
+    pub at_least: f64,
+    pub score: u8,
+}
+
+/// Lowest final score for each label; anything below `mixed` is human.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Labels {
+    /// "mixed", or "uncertain" when only the heuristics voted.
+    pub mixed: u8,
+    pub likely_ai: u8,
+    pub ai: u8,
+}
+
+/// Combination of the detectors that ran on a post.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnsembleRules {
+    pub strategy: Strategy,
+    /// Detector name -> weight (`weighted_mean`) or coefficient (`stacking`).
+    /// Detectors left out weigh 1.0.
+    #[serde(default)]
+    pub weights: BTreeMap<String, f64>,
+    /// Logistic intercept for `stacking`.
+    #[serde(default)]
+    pub intercept: f64,
+    #[serde(default)]
+    pub confidence: Confidence,
+}
+
+/// How the final confidence is derived from the detectors'.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    /// `0.3 + 0.7 *` the LLM provider's confidence; the heuristics' without a provider.
+    #[default]
+    Provider,
+    /// Weighted mean of the detectors' confidences; for `max`, the confidence
+    /// of the detector that set the score.
+    Detectors,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Weighted average of the scores.
+    WeightedMean,
+    /// The highest score.
+    Max,
+    /// `10 * sigmoid(intercept + sum(weight * score))`, for weights fitted offline.
+    Stacking,
+}
+
+/// Per-platform adjustments. Anything left out falls back to the global rules.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Measurement -> tiers replacing the global tiers. An empty list silences it.
+    #[serde(default)]
+    pub signals: BTreeMap<String, Vec<Tier>>,
+    /// Measurement -> multiplier on the weight of whichever tier votes.
+    #[serde(default)]
+    pub weights: BTreeMap<String, f64>,
+    pub labels: Option<Labels>,
+    /// Appended to the LLM system prompt.
+    pub prompt: Option<String>,
+}
+
+impl Tier {
+    pub fn matches(&self, value: f64) -> bool {
+        self.below.is_none_or(|b| value < b)
+            && self.above.is_none_or(|a| value > a)
+            && self.at_least.is_none_or(|a| value >= a)
+    }
+}
+
+impl Rules {
+    /// The rules shipped with the binary.
+    pub fn builtin() -> Self {
+        Self::parse(None).expect("Built-in rules are invalid")
+    }
+
+    /// Load rules from `path` layered over the built-in rules, or the built-in rules alone.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        match path {
+            Some(p) => {
+                let data = fs::read_to_string(p)
+                    .map_err(|e| format!("Failed to read {}: {e}", p.display()))?;
+                Self::parse(Some(&data))
+            }
+            None => Self::parse(None),
+        }
+    }
+
+    fn parse(overlay: Option<&str>) -> Result<Self, String> {
+        let mut table: toml::Table = BUILTIN_RULES
+            .parse()
+            .map_err(|e| format!("Built-in rules: {e}"))?;
+
+        if let Some(data) = overlay {
+            let user: toml::Table = data.parse().map_err(|e| format!("Invalid TOML: {e}"))?;
+            if !user.contains_key("version") {
+                return Err("Rules file must declare a `version`".to_string());
+            }
+            merge(&mut table, user);
+        }
+
+        let mut rules: Rules = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| format!("Invalid rules: {e}"))?;
+        rules.validate()?;
+        rules.compile()?;
+        Ok(rules)
+    }
+
+    /// Entries of lexicon `name` for `language`. Empty when the language has none.
+    pub fn lexicon(&self, language: Option<&str>, name: &str) -> &[String] {
+        language
+            .and_then(|lang| self.lexicons.get(lang))
+            .and_then(|set| set.get(name))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Compiled matcher for lexicon `name` in `language`, None when the language has none.
+    pub fn matcher(&self, language: Option<&str>, name: &str) -> Option<&Matcher> {
+        self.matchers.get(language?)?.get(name)
+    }
+
+    pub fn has_lexicons(&self, language: &str) -> bool {
+        self.lexicons.contains_key(language)
+    }
+
+    pub fn tiers(&self, measurement: &str) -> &[Tier] {
+        self.signals.get(measurement).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Scoring profile for `platform`, None when it has none.
+    pub fn profile(&self, platform: Option<&Platform>) -> Option<&Profile> {
+        self.platforms.get(&platform?.to_string())
+    }
+
+    /// Tiers of `measurement` on `platform`: the profile's own, else the global ones.
+    pub fn tiers_for(&self, platform: Option<&Platform>, measurement: &str) -> &[Tier] {
+        self.profile(platform)
+            .and_then(|p| p.signals.get(measurement))
+            .map(Vec::as_slice)
+            .unwrap_or_else(|| self.tiers(measurement))
+    }
+
+    /// Ensemble weight of detector `name`.
+    pub fn detector_weight(&self, name: &str) -> f64 {
+        self.ensemble.weights.get(name).copied().unwrap_or(1.0)
+    }
+
+    /// Multiplier on the vote weight of `measurement` on `platform`.
+    pub fn weight_scale(&self, platform: Option<&Platform>, measurement: &str) -> f64 {
+        self.profile(platform)
+            .and_then(|p| p.weights.get(measurement))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    pub fn labels(&self, platform: Option<&Platform>) -> &Labels {
+        self.profile(platform)
+            .and_then(|p| p.labels.as_ref())
+            .unwrap_or(&self.labels)
+    }
+
+    /// Platform-specific instructions for the LLM.
+    pub fn prompt(&self, platform: Option<&Platform>) -> Option<&str> {
+        self.profile(platform)?.prompt.as_deref()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.version.trim().is_empty() {
+            return Err("`version` cannot be empty".to_string());
+        }
+        check_vote("prior", self.prior.score, self.prior.weight)?;
+
+        if !self.has_lexicons(&self.language.default) {
+            return Err(format!("No lexicons for default language `{}`", self.language.default));
+        }
+        if !(0.0..=1.0).contains(&self.language.unsupported_confidence) {
+            return Err("language.unsupported_confidence must be 0-1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.short_text.confidence) {
+            return Err("short_text.confidence must be 0-1".to_string());
+        }
+        if !self.watermark.z_threshold.is_finite() || self.watermark.z_threshold <= 0.0 {
+            return Err("watermark.z_threshold must be a positive number".to_string());
+        }
+        let author = &self.author_baseline;
+        if author.min_posts < 2 || author.max_posts < author.min_posts {
+            return Err("author_baseline needs 2 <= min_posts <= max_posts".to_string());
+        }
+        if !(author.habitual > 0.0 && author.habitual <= 1.0) {
+            return Err("author_baseline.habitual must be above 0 and at most 1".to_string());
+        }
+        if !(self.templates.min_similarity > 0.0 && self.templates.min_similarity <= 1.0) {
+            return Err("templates.min_similarity must be above 0 and at most 1".to_string());
+        }
+        if self.templates.lookback_days < 1 {
+            return Err("templates.lookback_days must be at least 1".to_string());
+        }
+        if self.neighbours.k == 0 {
+            return Err("neighbours.k must be at least 1".to_string());
+        }
+        if !(-1.0..1.0).contains(&self.neighbours.min_similarity) {
+            return Err("neighbours.min_similarity must be at least -1 and below 1".to_string());
+        }
+        let campaigns = &self.campaigns;
+        if !(0..=10).contains(&campaigns.min_score) {
+            return Err("campaigns.min_score must be between 0 and 10".to_string());
+        }
+        if campaigns.window_minutes < 1 || campaigns.lookback_days < 1 {
+            return Err("campaigns.window_minutes and campaigns.lookback_days must be at least 1".to_string());
+        }
+        if !(campaigns.min_signal_overlap > 0.0 && campaigns.min_signal_overlap <= 1.0) {
+            return Err("campaigns.min_signal_overlap must be above 0 and at most 1".to_string());
+        }
+        if campaigns.min_authors < 2 {
+            return Err("campaigns.min_authors must be at least 2".to_string());
+        }
+        if let Some(m) = author.waive_floors.iter().find(|m| !heuristics::MEASUREMENTS.contains(&m.as_str())) {
+            return Err(format!("author_baseline.waive_floors references unknown measurement `{m}`"));
+        }
+
+        for (lang, set) in &self.lexicons {
+            for (name, entries) in set {
+                if !LEXICONS.contains(&name.as_str()) {
+                    return Err(format!("Unknown lexicon `{lang}.{name}`"));
+                }
+                for entry in entries {
+                    if entry.trim().is_empty() {
+                        return Err(format!("Lexicon `{lang}.{name}` contains an empty entry"));
+                    }
+                    if entry.to_lowercase() != *entry {
+                        return Err(format!("Lexicon `{lang}.{name}` entry {entry:?} must be lowercase"));
+                    }
+                }
+            }
+        }
+
+        check_signals("signals", &self.signals)?;
+        check_labels("labels", &self.labels)?;
+
+        for (name, weight) in &self.ensemble.weights {
+            if !DETECTORS.contains(&name.as_str()) {
+                return Err(format!("ensemble.weights references unknown detector `{name}`"));
+            }
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(format!("ensemble.weights.{name} must be a non-negative number"));
+            }
+        }
+        if !self.ensemble.intercept.is_finite() {
+            return Err("ensemble.intercept must be a number".to_string());
+        }
+
+        for (name, profile) in &self.platforms {
+            if !Platform::ALL.iter().any(|p| p.to_string() == *name) {
+                return Err(format!("Unknown platform `{name}`"));
+            }
+            let at = format!("platforms.{name}");
+            check_signals(&format!("{at}.signals"), &profile.signals)?;
+            for (measurement, scale) in &profile.weights {
+                if !is_measurement(measurement) {
+                    return Err(format!("{at}.weights references unknown measurement `{measurement}`"));
+                }
+                if !scale.is_finite() || *scale < 0.0 {
+                    return Err(format!("{at}.weights.{measurement} must be a non-negative number"));
+                }
+            }
+            if let Some(labels) = &profile.labels {
+                check_labels(&format!("{at}.labels"), labels)?;
+            }
+            if profile.prompt.as_deref().is_some_and(|p| p.trim().is_empty()) {
+                return Err(format!("{at}.prompt cannot be empty"));
+            }
+        }
+
+        for (i, floor) in self.floors.iter().enumerate() {
+            if !heuristics::MEASUREMENTS.contains(&floor.measurement.as_str()) {
+                return Err(format!("floors[{i}] references unknown measurement `{}`", floor.measurement));
+            }
+            if floor.score > 10 {
+                return Err(format!("floors[{i}] score must be 0-10"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile(&mut self) -> Result<(), String> {
+        for (lang, set) in &self.lexicons {
+            for (name, entries) in set {
+                let matcher = Matcher::new(entries).map_err(|e| format!("Lexicon `{lang}.{name}`: {e}"))?;
+                self.matchers
+                    .entry(lang.clone())
+                    .or_default()
+                    .insert(name.clone(), matcher);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_signals(at: &str, signals: &BTreeMap<String, Vec<Tier>>) -> Result<(), String> {
+    for (name, tiers) in signals {
+        if !is_measurement(name) {
+            return Err(format!("Unknown signal `{name}` in `{at}`"));
+        }
+        for (i, tier) in tiers.iter().enumerate() {
+            let at = format!("{at}.{name}[{i}]");
+            if tier.below.is_none() && tier.above.is_none() && tier.at_least.is_none() {
+                return Err(format!("{at} needs at least one of `below`, `above`, `at_least`"));
+            }
+            if tier.signal.as_deref().is_some_and(|s| s.trim().is_empty()) {
+                return Err(format!("{at} has an empty `signal`"));
+            }
+            check_vote(&at, tier.score, tier.weight)?;
+        }
+    }
+    Ok(())
+}
+
+/// Measurements the rules file may attach signal tiers to. Besides the heuristic
+/// ones, each detector and context that votes by the rules' tiers (through
+/// `ensemble::vote` or the heuristic engine's context) registers its own here.
+pub const MEASUREMENTS: &[&[&str]] = &[
+    heuristics::MEASUREMENTS,
+    baseline::MEASUREMENTS,
+    templates::MEASUREMENTS,
+    perplexity::MEASUREMENTS,
+    logprobs::MEASUREMENTS,
+];
+
+fn is_measurement(name: &str) -> bool {
+    MEASUREMENTS.iter().any(|names| names.contains(&name))
+}
+
+fn check_labels(at: &str, labels: &Labels) -> Result<(), String> {
+    if !(0 < labels.mixed && labels.mixed < labels.likely_ai && labels.likely_ai < labels.ai && labels.ai <= 10) {
+        return Err(format!("{at} must satisfy 0 < mixed < likely_ai < ai <= 10"));
+    }
+    Ok(())
+}
+
+fn check_vote(at: &str, score: f64, weight: f64) -> Result<(), String> {
+    if !(0.0..=10.0).contains(&score) {
+        return Err(format!("{at} score must be 0-10"));
+    }
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!("{at} weight must be a non-negative number"));
+    }
+    Ok(())
+}
+
+/// Recursively merge `overlay` into `base`. Tables merge key by key, anything else is replaced.
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(b)), toml::Value::Table(o)) => merge(b, o),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Shared, hot-swappable rules. Readers grab an `Arc` snapshot per analysis.
+#[derive(Clone)]
+pub struct RulesStore {
+    inner: Arc<RwLock<Arc<Rules>>>,
+}
+
+impl RulesStore {
+    pub fn new(rules: Rules) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Arc::new(rules))),
+        }
+    }
+
+    pub fn current(&self) -> Arc<Rules> {
+        self.inner.read().expect("Rules lock poisoned").clone()
+    }
+
+    fn replace(&self, rules: Rules) {
+        *self.inner.write().expect("Rules lock poisoned") = Arc::new(rules);
+    }
+}
+
+/// Poll `path` for changes and swap in the new rules when they validate.
+/// Invalid edits are logged and the previous rules stay active.
+pub fn spawn_watcher(store: RulesStore, path: PathBuf, interval: Duration) {
+    tokio::spawn(async move {
+        let mut last_modified = modified_at(&path);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let modified = modified_at(&path);
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            match Rules::load(Some(&path)) {
+                Ok(rules) => {
+                    tracing::info!("Reloaded rules from {} (version {})", path.display(), rules.version);
+                    store.replace(rules);
+                }
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid rules in {}: {e}", path.display());
+                }
+            }
+        }
+    });
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_rules_are_valid() {
+        let rules = Rules::builtin();
+        assert!(!rules.lexicon(Some("en"), "formulaic_phrases").is_empty());
+        assert!(!rules.lexicon(Some("es"), "formulaic_phrases").is_empty());
+        assert!(rules.lexicon(None, "formulaic_phrases").is_empty());
+        assert!(!rules.tiers("em_en_dash").is_empty());
+    }
+
+    #[test]
+    fn test_overlay_replaces_only_given_tables() {
+        let rules = Rules::parse(Some(
+            r#"
+            version = "custom-1"
+
+            [lexicons.en]
+            ai_vocabulary = ["delve"]
+
+            [[signals.em_en_dash]]
+            at_least = 1.0
+            signal = "em_en_dash"
+            score = 7.0
+            weight = 1.0
+            "#,
+        ))
+        .unwrap();
+        assert_eq!(rules.version, "custom-1");
+        assert_eq!(rules.lexicon(Some("en"), "ai_vocabulary"), ["delve"]);
+        assert!(!rules.lexicon(Some("en"), "human_slang").is_empty());
+        assert_eq!(rules.tiers("em_en_dash")[0].score, 7.0);
+        assert_eq!(rules.tiers("burstiness").len(), 2);
+    }
+
+    #[test]
+    fn test_platform_profiles() {
+        let rules = Rules::parse(Some(
+            r#"
+            version = "custom-2"
+
+            [platforms.twitter.weights]
+            burstiness = 0.5
+
+            [platforms.linkedin]
+            prompt = "Custom LinkedIn guidance."
+            labels = { mixed = 3, likely_ai = 5, ai = 7 }
+            "#,
+        ))
+        .unwrap();
+        let (twitter, instagram, linkedin) = (Some(&Platform::Twitter), Some(&Platform::Instagram), Some(&Platform::LinkedIn));
+
+        // Overlay merges into the built-in profiles key by key
+        assert_eq!(rules.weight_scale(twitter, "burstiness"), 0.5);
+        assert!(rules.tiers_for(twitter, "linebreak_ratio").is_empty());
+        assert!(!rules.tiers_for(None, "linebreak_ratio").is_empty());
+        assert!(rules.tiers_for(instagram, "trailing_hashtags").is_empty());
+        assert_eq!(rules.tiers_for(instagram, "em_en_dash").len(), rules.tiers("em_en_dash").len());
+        assert_eq!(rules.weight_scale(None, "burstiness"), 1.0);
+
+        assert_eq!(rules.labels(linkedin).likely_ai, 5);
+        assert_eq!(rules.labels(None).likely_ai, 6);
+        assert_eq!(rules.prompt(linkedin), Some("Custom LinkedIn guidance."));
+        assert!(rules.prompt(None).is_none());
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        assert!(Rules::parse(Some("[prior]\nscore = 1.0\nweight = 1.0")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[lexicons.en]\nhuman_slang = [\"LOL\"]")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[lexicons.en]\nhuman_slang = [\"lol{\"]")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[[signals.nope]]\nbelow = 1.0\nscore = 1.0\nweight = 1.0")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[[signals.burstiness]]\nscore = 1.0\nweight = 1.0")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[language]\ndefault = \"xx\"\nunsupported_confidence = 0.5")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[short_text]\nbelow = 20\nconfidence = 1.5")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[[floors]]\nmeasurement = \"em_en_dash\"\nat_least = 1.0\nscore = 11")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[labels]\nmixed = 6\nlikely_ai = 6\nai = 8")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[ensemble.weights]\nmystery = 1.0")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[ensemble]\nstrategy = \"median\"")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[platforms.myspace]\nprompt = \"hi\"")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[platforms.twitter.weights]\nnope = 1.0")).is_err());
+        assert!(Rules::parse(Some("version = \"x\"\n[[platforms.twitter.signals.nope]]\nbelow = 1.0\nscore = 1.0\nweight = 1.0")).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! Sentence segmenter for social media text.
+//!
+//! Splitting on every `.`, `!` or `?` turns "$1.5M", "e.g.", "3.5x",
+//! "example.com" and "..." into fake sentences. This segmenter only ends a
+//! sentence on a terminator that is followed by whitespace (or the end of the
+//! text), skips abbreviations, initials and numbered-list markers, treats
+//! ellipses as pauses unless a capitalized sentence follows, keeps trailing
+//! emoji with the sentence they close, and understands line-based formatting:
+//! blank lines, bullet items and standalone lines each end a sentence, while
+//! a line continuing in lowercase is a wrapped sentence.
+
+use std::ops::Range;
+
+use crate::services::text;
+
+/// Abbreviations that never end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "e.g", "i.e", "cf", "approx",
+    "dept", "est", "fig", "no", "vol", "ave", "blvd", "jan", "feb", "apr", "jun", "jul", "aug",
+    "sep", "sept", "oct", "nov", "dec", "u.s", "u.k", "ph.d",
+];
+
+/// Abbreviations that end a sentence only when a capitalized word follows.
+const FINAL_ABBREVIATIONS: &[&str] = &["etc", "inc", "ltd", "co", "corp", "a.m", "p.m"];
+
+/// Bullet characters that start a list item.
+const BULLETS: &[char] = &[
+    '•', '▸', '▹', '▪', '▫', '►', '◦', '‣', '·', '-', '–', '—', '*', '+', '→', '✓', '✔', '✅', '☑',
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sentence<'a> {
+    /// Sentence text, trimmed.
+    pub text: &'a str,
+    /// Byte offset of `text` in the segmented content.
+    pub start: usize,
+    /// Terminator that ended the sentence (`…` for any ellipsis), None for
+    /// sentences ended by a line break or the end of the text.
+    pub terminator: Option<char>,
+    /// Starts with a bullet, emoji bullet or number marker.
+    pub list_item: bool,
+    /// Starts at the beginning of a line and ends at the end of it.
+    pub own_line: bool,
+}
+
+/// Split `text` into sentences. Fragments without any word are dropped.
+pub fn segment(text: &str) -> Vec<Sentence<'_>> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let lines = Lines::new(text);
+    let content_end = text.trim_end().len();
+    let mut out = Vec::new();
+    let mut start = 0;
+    // Whether anything but whitespace was seen since `start`
+    let mut seen = false;
+    // First word character at or after the last terminator run that looked ahead
+    let mut word = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (pos, ch) = chars[i];
+
+        if ch == '\n' {
+            if seen && line_break_ends_sentence(text, &lines, start, pos) {
+                push(&mut out, text, &lines, start, pos, None);
+                start = pos;
+                seen = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        if !text::is_terminator(ch) {
+            seen |= !ch.is_whitespace();
+            i += 1;
+            continue;
+        }
+        seen = true;
+
+        // Consume the whole terminator run ("?!", "...", "。")
+        let run_start = i;
+        while i < chars.len() && text::is_terminator(chars[i].1) {
+            i += 1;
+        }
+        let run: String = chars[run_start..i].iter().map(|(_, c)| c).collect();
+        let ellipsis = run.contains('…') || run.contains("..");
+        let cjk = run.chars().all(|c| !c.is_ascii() && c != '…');
+
+        // Closing quotes / brackets and trailing emoji belong to this sentence
+        while i < chars.len() && is_closer(chars[i].1) {
+            i += 1;
+        }
+        loop {
+            let mut j = i;
+            while j < chars.len() && chars[j].1 == ' ' {
+                j += 1;
+            }
+            if j < chars.len() && text::is_emoji(chars[j].1) {
+                i = j + 1;
+            } else {
+                break;
+            }
+        }
+
+        let end = chars.get(i).map_or(text.len(), |(p, _)| *p);
+        let next = chars.get(i).map(|(_, c)| *c);
+        let followed_by_space = next.is_none_or(char::is_whitespace);
+
+        let boundary = if cjk {
+            true
+        } else if !followed_by_space {
+            // "3.5x", "example.com", "e.g.," — not an end
+            false
+        } else {
+            // The cursor only moves forward, so the look-ahead stays linear
+            word = word.max(i);
+            while word < chars.len() && !(chars[word].1.is_alphanumeric() || text::is_emoji(chars[word].1)) {
+                word += 1;
+            }
+            let capitalized = chars.get(word).is_some_and(|(_, c)| !c.is_lowercase());
+            if ellipsis {
+                capitalized || next.is_none()
+            } else if run == "." {
+                period_ends_sentence(text, &lines, start, chars[run_start].0, capitalized || end >= content_end)
+            } else {
+                true
+            }
+        };
+
+        if boundary {
+            let terminator = if ellipsis { '…' } else { run.chars().last().unwrap_or('.') };
+            push(&mut out, text, &lines, start, end, Some(terminator));
+            start = end;
+            seen = false;
+        }
+    }
+
+    push(&mut out, text, &lines, start, text.len(), None);
+    out
+}
+
+/// Line starts and trimmed line contents, computed once so that line lookups
+/// don't rescan the text.
+struct Lines {
+    starts: Vec<usize>,
+    /// Byte range of each line without surrounding whitespace; empty for blank lines.
+    content: Vec<Range<usize>>,
+}
+
+impl Lines {
+    fn new(text: &str) -> Self {
+        let mut starts = Vec::new();
+        let mut content = Vec::new();
+        let mut start = 0;
+        for line in text.split('\n') {
+            let offset = start + (line.len() - line.trim_start().len());
+            starts.push(start);
+            content.push(offset..offset + line.trim().len());
+            start += line.len() + 1;
+        }
+        Self { starts, content }
+    }
+
+    /// Index of the line containing byte `offset` (a line break belongs to the line it ends).
+    fn line(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&s| s <= offset) - 1
+    }
+
+    /// Line `n` without its line break.
+    fn text<'a>(&self, text: &'a str, n: usize) -> &'a str {
+        let end = self.starts.get(n + 1).map_or(text.len(), |s| s - 1);
+        &text[self.starts[n]..end]
+    }
+}
+
+/// Whether a line starts with a list marker: a bullet, an emoji, or "1." / "1)".
+pub fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    let Some(first) = line.chars().next() else {
+        return false;
+    };
+    if BULLETS.contains(&first) {
+        // "-5%" or "—" inside prose is not a bullet; a bullet is followed by a space
+        return line[first.len_utf8()..].starts_with([' ', '\t']);
+    }
+    if text::is_emoji(first) {
+        return true;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    (1..=3).contains(&digits)
+        && line[digits..].starts_with(['.', ')'])
+        && line[digits + 1..].starts_with(' ')
+}
+
+fn push<'a>(
+    out: &mut Vec<Sentence<'a>>,
+    text: &'a str,
+    lines: &Lines,
+    start: usize,
+    end: usize,
+    terminator: Option<char>,
+) {
+    let raw = &text[start..end];
+    let trimmed = raw.trim();
+    if !trimmed.chars().any(char::is_alphanumeric) {
+        return;
+    }
+    let offset = start + (raw.len() - raw.trim_start().len());
+    let after = offset + trimmed.len();
+    let starts_line = lines.content[lines.line(offset)].start == offset;
+    let ends_line = lines.content[lines.line(after)].end <= after;
+
+    out.push(Sentence {
+        text: trimmed,
+        start: offset,
+        terminator,
+        list_item: is_list_item(trimmed),
+        own_line: starts_line && ends_line,
+    });
+}
+
+/// A line break at `pos` ends the current sentence (which began at `start`) unless
+/// the next line continues it in lowercase.
+fn line_break_ends_sentence(text: &str, lines: &Lines, start: usize, pos: usize) -> bool {
+    let line = lines.line(pos);
+    let current_line = &text[lines.starts[line].max(start)..pos];
+    let next_line = lines.text(text, line + 1);
+
+    if lines.content[line + 1].is_empty() || is_list_item(current_line) || is_list_item(next_line) {
+        return true;
+    }
+    if current_line.trim_end().ends_with([',', ';']) {
+        return false;
+    }
+    !next_line.trim_start().starts_with(char::is_lowercase)
+}
+
+/// Decide whether a single "." at `dot` ends a sentence, given the text before
+/// it (from the sentence start) and whether a capitalized word or nothing follows.
+fn period_ends_sentence(text: &str, lines: &Lines, start: usize, dot: usize, next_sentence: bool) -> bool {
+    let before = &text[start..dot];
+    let word = before.rsplit(char::is_whitespace).next().unwrap_or("");
+    let token = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let lower = token.to_lowercase();
+
+    if ABBREVIATIONS.contains(&lower.as_str()) {
+        return false;
+    }
+    if FINAL_ABBREVIATIONS.contains(&lower.as_str()) {
+        return next_sentence;
+    }
+    // Initials ("J. K. Rowling")
+    if token.chars().count() == 1 && token.chars().all(char::is_uppercase) {
+        return false;
+    }
+    // Numbered list marker at line start ("1. Wake up")
+    let line_start = lines.content[lines.line(dot)].start;
+    if !word.is_empty() && dot - word.len() == line_start && word.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    true
+}
+
+fn is_closer(c: char) -> bool {
+    matches!(c, '"' | '\'' | '”' | '’' | '»' | ')' | ']' | '」' | '』' | '）' | '】')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn texts(text: &str) -> Vec<&str> {
+        segment(text).into_iter().map(|s| s.text).collect()
+    }
+
+    #[test]
+    fn test_plain_sentences() {
+        assert_eq!(
+            texts("One thing. Another thing! A third? Yes."),
+            ["One thing.", "Another thing!", "A third?", "Yes."]
+        );
+    }
+
+    #[test]
+    fn test_numbers_and_money_do_not_split() {
+        assert_eq!(
+            texts("It sold for €1.5M. That's 3.5x the price, up 68.7% in a year."),
+            ["It sold for €1.5M.", "That's 3.5x the price, up 68.7% in a year."]
+        );
+    }
+
+    #[test]
+    fn test_abbreviations_and_initials() {
+        assert_eq!(
+            texts("Tools e.g. Rust and Go work. Ask Dr. Smith or J. K. Rowling about it."),
+            ["Tools e.g. Rust and Go work.", "Ask Dr. Smith or J. K. Rowling about it."]
+        );
+        assert_eq!(
+            texts("We sell apples, pears, etc. Then we go home."),
+            ["We sell apples, pears, etc.", "Then we go home."]
+        );
+    }
+
+    #[test]
+    fn test_urls_and_domains() {
+        assert_eq!(
+            texts("Check example.com/docs?x=1 for more. Or visit https://a.b.io/path. Thanks"),
+            ["Check example.com/docs?x=1 for more.", "Or visit https://a.b.io/path.", "Thanks"]
+        );
+    }
+
+    #[test]
+    fn test_ellipsis() {
+        assert_eq!(
+            texts("My cat knocked over my coffee... again. Then... Nothing happened."),
+            ["My cat knocked over my coffee... again.", "Then...", "Nothing happened."]
+        );
+        assert_eq!(segment("Wait for it…").len(), 1);
+    }
+
+    #[test]
+    fn test_emoji_stays_with_sentence() {
+        assert_eq!(
+            texts("Shipped it! 🚀🚀 Next up is docs."),
+            ["Shipped it! 🚀🚀", "Next up is docs."]
+        );
+    }
+
+    #[test]
+    fn test_line_based_formatting() {
+        let text = "Success is not about hours\n\
+                    It is about systems\n\n\
+                    Here are the habits:\n\
+                    1. Wake up at 5 AM\n\
+                    2. Read daily\n\
+                    • Network with purpose\n\
+                    this line wraps\n\
+                    onto the next";
+        let sentences = segment(text);
+        let texts: Vec<&str> = sentences.iter().map(|s| s.text).collect();
+        assert_eq!(
+            texts,
+            [
+                "Success is not about hours",
+                "It is about systems",
+                "Here are the habits:",
+                "1. Wake up at 5 AM",
+                "2. Read daily",
+                "• Network with purpose",
+                "this line wraps\nonto the next",
+            ]
+        );
+        assert_eq!(sentences.iter().filter(|s| s.list_item).count(), 3);
+        assert!(sentences.iter().all(|s| s.own_line));
+    }
+
+    #[test]
+    fn test_cjk_terminators() {
+        assert_eq!(texts("今日は晴れ。明日は雨！本当？"), ["今日は晴れ。", "明日は雨！", "本当？"]);
+    }
+
+    #[test]
+    fn test_long_posts_segment_in_linear_time() {
+        let started = Instant::now();
+        let lines = format!("Hello{}world", "\n".repeat(49_990));
+        assert_eq!(texts(&lines), ["Hello", "world"]);
+        let sentences = " a.".repeat(16_600);
+        assert_eq!(segment(&sentences).len(), 16_600);
+        let numbered = format!("{} a.", "1".repeat(40_000)) + &" b.".repeat(3_000);
+        assert_eq!(segment(&numbered).len(), 3_001);
+        let ellipses = format!("Wait{} ok", " ...".repeat(12_500));
+        assert_eq!(segment(&ellipses).len(), 1);
+        // Quadratic rescans took seconds on these
+        assert!(started.elapsed() < Duration::from_millis(500), "{:?}", started.elapsed());
+    }
+
+    #[test]
+    fn test_offsets_and_terminators() {
+        let text = "  Hi there!  Bye now...";
+        let sentences = segment(text);
+        assert_eq!(sentences[0].start, 2);
+        assert_eq!(&text[sentences[1].start..], "Bye now...");
+        assert_eq!(sentences[0].terminator, Some('!'));
+        assert_eq!(sentences[1].terminator, Some('…'));
+    }
+}
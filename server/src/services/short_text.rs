@@ -0,0 +1,132 @@
+//! Features for posts too short for text statistics: replies, reactions and
+//! one-line tweets.
+//!
+//! Sentence variance, vocabulary diversity and stylometry need dozens of words.
+//! What a ten-word reply does show is whether it is a stock compliment
+//! ("Great insights! Thanks for sharing."), an emoji-only reaction, an
+//! engagement-bait template ("Who else agrees?"), and how it is cased and
+//! punctuated. Reply bots write tidy, capitalized, fully punctuated sentences;
+//! people thumbing out a reply often don't.
+
+use std::ops::Range;
+
+use crate::services::matcher::Matcher;
+use crate::services::stats::TextStats;
+use crate::services::text;
+
+/// Lexicon matchers for the post's language. Missing lexicons skip their feature.
+pub struct Lexicons<'a> {
+    pub generic_replies: Option<&'a Matcher>,
+    pub engagement_bait: Option<&'a Matcher>,
+}
+
+#[derive(Debug, Default)]
+pub struct ShortText {
+    /// Share of words inside a stock reply phrase.
+    pub generic_praise: Option<f64>,
+    pub generic_phrases: Vec<Range<usize>>,
+    /// Engagement-bait phrases ("like if you agree", "who else").
+    pub engagement_bait: Vec<Range<usize>>,
+    /// Emoji of a post made of emoji and nothing else.
+    pub emoji_only: Vec<Range<usize>>,
+    /// Share of sentences opening with a capital letter; None without cased letters.
+    pub capitalized: Option<f64>,
+    /// Share of sentences closed by terminal punctuation.
+    pub terminated: Option<f64>,
+}
+
+/// Analyze the short post `stats.text`. Spans are byte ranges into it.
+pub fn analyze(stats: &TextStats, lexicons: &Lexicons) -> ShortText {
+    let find = |matcher: Option<&Matcher>| -> Option<Vec<Range<usize>>> {
+        Some(matcher?.find(&stats.lower.text).into_iter().map(|(_, span)| span).collect())
+    };
+
+    let generic = find(lexicons.generic_replies);
+    let generic_praise = generic.as_ref().filter(|_| stats.word_count() > 0).map(|spans| {
+        let covered = stats
+            .lower_words()
+            .filter(|(i, w)| spans.iter().any(|s| s.start <= *i && i + w.len() <= s.end))
+            .count();
+        covered as f64 / stats.word_count() as f64
+    });
+    let source = |spans: Vec<Range<usize>>| -> Vec<Range<usize>> {
+        spans.into_iter().map(|s| stats.lower.span(s.start, s.end)).collect()
+    };
+
+    let capitals: Vec<bool> = stats
+        .sentences
+        .iter()
+        .filter_map(|s| s.text.chars().find(|c| c.is_uppercase() || c.is_lowercase()))
+        .map(char::is_uppercase)
+        .collect();
+
+    ShortText {
+        generic_praise,
+        generic_phrases: source(generic.unwrap_or_default()),
+        engagement_bait: source(find(lexicons.engagement_bait).unwrap_or_default()),
+        emoji_only: emoji_only(stats),
+        capitalized: share(&capitals),
+        terminated: share(&stats.sentences.iter().map(|s| s.terminator.is_some()).collect::<Vec<_>>()),
+    }
+}
+
+/// Emoji spans when the post holds emoji and no words.
+fn emoji_only(stats: &TextStats) -> Vec<Range<usize>> {
+    if stats.word_count() > 0 {
+        return Vec::new();
+    }
+    stats
+        .text
+        .char_indices()
+        .filter(|(_, c)| text::is_emoji(*c) && !matches!(c, '\u{FE0F}' | '\u{200D}' | '\u{20E3}'))
+        .map(|(i, c)| i..i + c.len_utf8())
+        .collect()
+}
+
+fn share(flags: &[bool]) -> Option<f64> {
+    (!flags.is_empty()).then(|| flags.iter().filter(|&&f| f).count() as f64 / flags.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(entries: &[&str]) -> Matcher {
+        Matcher::new(&entries.iter().map(|e| e.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn analyze_with(text: &str) -> ShortText {
+        let generic = matcher(&["great insight{,s}", "thanks for sharing", "so true"]);
+        let bait = matcher(&["who else", "like if you agree"]);
+        let lexicons = Lexicons {
+            generic_replies: Some(&generic),
+            engagement_bait: Some(&bait),
+        };
+        analyze(&TextStats::new(text), &lexicons)
+    }
+
+    #[test]
+    fn test_generic_reply() {
+        let text = "Great insights! Thanks for sharing.";
+        let s = analyze_with(text);
+        assert_eq!(s.generic_praise, Some(1.0));
+        let phrases: Vec<&str> = s.generic_phrases.iter().map(|r| &text[r.clone()]).collect();
+        assert_eq!(phrases, ["Great insights", "Thanks for sharing"]);
+        assert_eq!((s.capitalized, s.terminated), (Some(1.0), Some(1.0)));
+    }
+
+    #[test]
+    fn test_bait_emoji_and_casual_habits() {
+        let s = analyze_with("Who else is tired of meetings?");
+        assert_eq!(s.engagement_bait.len(), 1);
+        assert_eq!(s.generic_praise, Some(0.0));
+
+        let s = analyze_with("🔥🔥🙌");
+        assert_eq!(s.emoji_only.len(), 3);
+        assert_eq!(s.generic_praise, None);
+
+        let s = analyze_with("lol my cat did the same thing");
+        assert_eq!((s.capitalized, s.terminated), (Some(0.0), Some(0.0)));
+        assert!(s.emoji_only.is_empty());
+    }
+}
@@ -0,0 +1,133 @@
+//! Everything the heuristics read about a text, computed in one pass.
+//!
+//! Signals used to re-lowercase and re-split the same text over and over; on a
+//! 50,000-char payload that dominated analysis time. `TextStats` tokenizes,
+//! segments and lowercases once, and every signal reads from it.
+
+use std::ops::Range;
+
+use crate::services::segmenter::{self, Sentence};
+use crate::services::text;
+
+pub struct TextStats<'a> {
+    pub text: &'a str,
+    /// Lowercased copy with offsets back into `text`.
+    pub lower: Lowered,
+    /// Word tokens with byte offsets into `text`.
+    pub words: Vec<(usize, &'a str)>,
+    /// Word tokens as byte ranges into `lower.text`.
+    lower_words: Vec<Range<usize>>,
+    pub sentences: Vec<Sentence<'a>>,
+    /// Word count of each sentence, aligned with `sentences`.
+    pub sentence_words: Vec<usize>,
+    /// Non-empty lines, trimmed.
+    pub lines: Vec<&'a str>,
+    pub char_count: usize,
+    /// Commas, including the CJK forms `、` and `，`.
+    pub commas: usize,
+}
+
+impl<'a> TextStats<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let lower = Lowered::new(text);
+        let words = text::word_indices(text);
+        let lower_words = words
+            .iter()
+            .map(|(i, w)| lower.offset(*i)..lower.offset(i + w.len()))
+            .collect();
+        let sentences = segmenter::segment(text);
+        let starts: Vec<usize> = words.iter().map(|(i, _)| *i).collect();
+        let sentence_words = sentences
+            .iter()
+            .map(|s| {
+                let from = starts.partition_point(|&i| i < s.start);
+                let to = starts.partition_point(|&i| i < s.start + s.text.len());
+                to - from
+            })
+            .collect();
+
+        let mut char_count = 0;
+        let mut commas = 0;
+        for ch in text.chars() {
+            char_count += 1;
+            if matches!(ch, ',' | '、' | '，') {
+                commas += 1;
+            }
+        }
+
+        Self {
+            text,
+            lower,
+            words,
+            lower_words,
+            sentences,
+            sentence_words,
+            lines: text.lines().map(str::trim).filter(|l| !l.is_empty()).collect(),
+            char_count,
+            commas,
+        }
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Lowercased word tokens with byte offsets into `lower.text`.
+    pub fn lower_words(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.lower_words
+            .iter()
+            .map(|r| (r.start, &self.lower.text[r.clone()]))
+    }
+}
+
+/// Lowercased copy of the text with a map back to byte offsets in the original,
+/// since lowercasing can change the byte length of some characters.
+pub struct Lowered {
+    pub text: String,
+    origin: Vec<usize>,
+}
+
+impl Lowered {
+    pub fn new(text: &str) -> Self {
+        let mut lower = String::with_capacity(text.len());
+        let mut origin = Vec::with_capacity(text.len() + 1);
+        for (i, ch) in text.char_indices() {
+            for lc in ch.to_lowercase() {
+                lower.push(lc);
+                origin.extend(std::iter::repeat_n(i, lc.len_utf8()));
+            }
+        }
+        origin.push(text.len());
+        Self { text: lower, origin }
+    }
+
+    /// Map a byte range in the lowercased text back to the original text.
+    pub fn span(&self, start: usize, end: usize) -> Range<usize> {
+        self.origin[start]..self.origin[end]
+    }
+
+    /// Byte offset in the lowercased text of the char starting at `original`.
+    fn offset(&self, original: usize) -> usize {
+        self.origin.partition_point(|&o| o < original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_agree_with_text_layer() {
+        let text = "Ünïcode TEXT, here. Second line,\n\nthird — İstanbul! Done";
+        let stats = TextStats::new(text);
+        assert_eq!(stats.word_count(), text::word_count(text));
+        assert_eq!(stats.sentences.len(), 4);
+        assert_eq!(stats.sentence_words, [3, 2, 2, 1]);
+        assert_eq!(stats.lines.len(), 2);
+        assert_eq!(stats.commas, 2);
+        assert_eq!(stats.char_count, text.chars().count());
+
+        let (i, word) = stats.lower_words().find(|(_, w)| w.starts_with('i')).unwrap();
+        assert_eq!(&text[stats.lower.span(i, i + word.len())], "İstanbul");
+    }
+}
@@ -0,0 +1,220 @@
+//! Post structure: how the text is laid out, independent of what it says.
+//!
+//! AI-written social posts lean on a recognizable template: a Title Case hook
+//! line, short paragraphs, bullet or numbered lists (often with emoji as
+//! bullets) and a closing call to action. The hashtag block that usually
+//! follows is an entity, counted in `entities` and cut before this runs.
+
+use std::ops::Range;
+
+use crate::services::matcher::Matcher;
+use crate::services::segmenter;
+use crate::services::stats::TextStats;
+use crate::services::text;
+
+/// Lowercase words that Title Case leaves uncapitalized.
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "is", "of", "on", "or", "the", "to",
+    "vs", "with",
+];
+
+#[derive(Debug, Default)]
+pub struct Structure {
+    /// Share of non-empty lines that are polished list items; 0 with fewer than 3.
+    pub list_density: f64,
+    /// Markers (bullets, numbers, emoji) of polished list items.
+    pub list_markers: Vec<Range<usize>>,
+    /// Emoji used as line bullets.
+    pub emoji_bullets: Vec<Range<usize>>,
+    /// The first line, when it is a Title Case hook.
+    pub title_case_hook: Option<Range<usize>>,
+    pub layout: Layout,
+}
+
+/// The hook / body / call-to-action template.
+#[derive(Debug, Default)]
+pub struct Layout {
+    /// A short opening line standing as its own paragraph.
+    pub hook: Option<Range<usize>>,
+    /// At least two paragraphs between the hook and the ending.
+    pub body: bool,
+    /// A closing call to action or question to the audience.
+    pub cta: Option<Range<usize>>,
+}
+
+impl Layout {
+    /// Number of template parts present, 0-3.
+    pub fn parts(&self) -> usize {
+        self.hook.is_some() as usize + self.body as usize + self.cta.is_some() as usize
+    }
+
+    pub fn spans(&self) -> Vec<Range<usize>> {
+        self.hook.iter().chain(&self.cta).cloned().collect()
+    }
+}
+
+/// Analyze the layout of `stats.text`. Spans are byte ranges into it.
+pub fn analyze(stats: &TextStats, calls_to_action: Option<&Matcher>) -> Structure {
+    let text = stats.text;
+    let lines = text::line_spans(text);
+
+    let mut list_markers = Vec::new();
+    let mut emoji_bullets = Vec::new();
+    for line in &lines {
+        let content = &text[line.clone()];
+        if !segmenter::is_list_item(content) {
+            continue;
+        }
+        let marker = line.start..line.start + marker_len(content);
+        if content.starts_with(text::is_emoji) {
+            emoji_bullets.push(marker.clone());
+        }
+        // The AI template capitalizes every item; lowercase shorthand bullets
+        // ("▸ scans btc price") are how people jot notes
+        let item = text[marker.end..line.end].chars().find(|c| c.is_alphabetic());
+        if item.is_none_or(|c| !c.is_lowercase()) {
+            list_markers.push(marker);
+        }
+    }
+    let list_density = if list_markers.len() >= 3 {
+        list_markers.len() as f64 / lines.len() as f64
+    } else {
+        0.0
+    };
+
+    let paragraphs = paragraph_spans(text);
+
+    Structure {
+        list_density,
+        list_markers,
+        emoji_bullets,
+        title_case_hook: lines.first().filter(|l| is_title_case(&text[(*l).clone()])).cloned(),
+        layout: layout(stats, &paragraphs, calls_to_action),
+    }
+}
+
+/// Byte ranges of blank-line separated paragraphs, trimmed.
+fn paragraph_spans(text: &str) -> Vec<Range<usize>> {
+    let mut out: Vec<Range<usize>> = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    for line in text::line_spans_with_blanks(text) {
+        match (line, current.as_mut()) {
+            (Some(l), Some(c)) => c.end = l.end,
+            (Some(l), None) => current = Some(l),
+            (None, _) => out.extend(current.take()),
+        }
+    }
+    out.extend(current);
+    out
+}
+
+/// Byte length of the list marker at the start of `line`.
+fn marker_len(line: &str) -> usize {
+    let first = line.chars().next().map_or(0, char::len_utf8);
+    if line.starts_with(|c: char| c.is_ascii_digit()) {
+        line.find(['.', ')']).map_or(first, |i| i + 1)
+    } else {
+        // Emoji bullets can be multi-codepoint (✅, 👉🏼)
+        line.find(|c: char| !text::is_emoji(c) && !matches!(c, '\u{1F3FB}'..='\u{1F3FF}'))
+            .filter(|&i| i > 0)
+            .unwrap_or(first)
+    }
+}
+
+/// A short line where (nearly) every major word is capitalized.
+fn is_title_case(line: &str) -> bool {
+    let words: Vec<&str> = text::word_indices(line).into_iter().map(|(_, w)| w).collect();
+    if !(4..=14).contains(&words.len()) {
+        return false;
+    }
+    let major: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|w| w.starts_with(char::is_alphabetic))
+        .filter(|w| !MINOR_WORDS.contains(&w.to_lowercase().as_str()))
+        .collect();
+    let capitalized = major.iter().filter(|w| w.starts_with(char::is_uppercase)).count();
+    major.len() >= 3 && capitalized as f64 / major.len() as f64 >= 0.8
+}
+
+fn layout(stats: &TextStats, paragraphs: &[Range<usize>], calls_to_action: Option<&Matcher>) -> Layout {
+    let text = stats.text;
+    let Some(first) = paragraphs.first() else {
+        return Layout::default();
+    };
+    let opening = &text[first.clone()];
+    let hook = (paragraphs.len() >= 2 && !opening.contains('\n') && text::word_count(opening) <= 15)
+        .then(|| first.clone());
+
+    let last = paragraphs.last().filter(|_| paragraphs.len() >= 2);
+    let cta = last.and_then(|last| {
+        let cta_match = calls_to_action.and_then(|m| {
+            m.find(&stats.lower.text)
+                .into_iter()
+                .map(|(_, span)| stats.lower.span(span.start, span.end))
+                .find(|span| span.start >= last.start)
+        });
+        cta_match.or_else(|| {
+            // A closing question to the audience ("Thoughts?", "What would you add?")
+            let ending = text[last.clone()].trim_end_matches(|c: char| text::is_emoji(c) || c.is_whitespace());
+            ending.ends_with(['?', '？']).then(|| {
+                let start = stats
+                    .sentences
+                    .iter()
+                    .rev()
+                    .find(|s| s.start >= last.start && s.start < last.start + ending.len())
+                    .map_or(last.start, |s| s.start);
+                start..last.start + ending.len()
+            })
+        })
+    });
+
+    Layout {
+        hook,
+        body: paragraphs.len() >= 4,
+        cta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(text: &str) -> Structure {
+        analyze(&TextStats::new(text), None)
+    }
+
+    #[test]
+    fn test_lists_and_emoji_bullets() {
+        let text = "Here are the 3 habits:\n🚀 Ship daily\n✅ Track metrics\n👉 Never stop learning\nThat's it.";
+        let s = structure(text);
+        assert_eq!(s.list_markers.len(), 3);
+        assert_eq!(s.emoji_bullets.len(), 3);
+        assert_eq!(&text[s.emoji_bullets[1].clone()], "✅");
+        assert!((s.list_density - 0.6).abs() < 1e-9);
+
+        // Two bullets aren't a list-heavy post, and lowercase shorthand bullets are notes
+        assert_eq!(structure("Notes:\n- One\n- Two").list_density, 0.0);
+        assert_eq!(structure("the loop:\n▸ scans\n▸ detects lag\n▸ exits").list_density, 0.0);
+    }
+
+    #[test]
+    fn test_title_case_hook() {
+        assert!(structure("The 5 Habits That Changed My Life Forever\n\nstuff").title_case_hook.is_some());
+        assert!(structure("Most people think success is about working hard\n\nstuff").title_case_hook.is_none());
+    }
+
+    #[test]
+    fn test_hook_body_cta_layout() {
+        let text = "Nobody talks about this.\n\n\
+                    I spent years chasing the wrong goals.\n\n\
+                    Then I changed one habit and everything shifted.\n\n\
+                    What's one habit that changed your life?";
+        let s = structure(text);
+        assert_eq!(s.layout.parts(), 3);
+        assert_eq!(&text[s.layout.cta.clone().unwrap()], "What's one habit that changed your life?");
+
+        let casual = structure("just shipped the thing lol\nnow sleep");
+        assert_eq!(casual.layout.parts(), 0);
+    }
+}
@@ -0,0 +1,185 @@
+//! Classic stylometric features: readability, syllable and word-length
+//! distributions, function-word rate, pronoun profile and contraction rate.
+//!
+//! LLM prose is tidy and mid-register: few contractions, long Latinate words,
+//! readability scores of an essay, and a lot of "you" aimed at the reader.
+//! People posting for themselves write "I", contract everything and drop
+//! function words. Readability and syllables use English formulas and are only
+//! computed for English; the rest read per-language lexicons.
+
+use crate::services::matcher::Matcher;
+use crate::services::stats::TextStats;
+
+/// Below this many words, rates and distributions are noise.
+const MIN_WORDS: usize = 40;
+
+/// Below this many pronouns, pronoun shares are noise.
+const MIN_PRONOUNS: usize = 4;
+
+/// Lexicon matchers for the post's language. Missing lexicons skip their feature.
+pub struct Lexicons<'a> {
+    pub function_words: Option<&'a Matcher>,
+    pub first_person: Option<&'a Matcher>,
+    pub second_person: Option<&'a Matcher>,
+    pub first_person_plural: Option<&'a Matcher>,
+    pub contractions: Option<&'a Matcher>,
+}
+
+#[derive(Debug, Default)]
+pub struct Stylometry {
+    /// Flesch-Kincaid grade level (English only).
+    pub flesch_kincaid: Option<f64>,
+    /// Gunning fog index (English only).
+    pub gunning_fog: Option<f64>,
+    /// Mean syllables per word (English only).
+    pub syllables_per_word: Option<f64>,
+    /// Mean word length in characters.
+    pub mean_word_length: Option<f64>,
+    /// Standard deviation of word length in characters.
+    pub word_length_spread: Option<f64>,
+    /// Share of words that are function words (articles, prepositions, ...).
+    pub function_word_rate: Option<f64>,
+    /// Shares of "I", "you" and "we" among those three pronoun groups.
+    pub first_person_share: Option<f64>,
+    pub second_person_share: Option<f64>,
+    pub first_person_plural_share: Option<f64>,
+    /// Contractions per word.
+    pub contraction_rate: Option<f64>,
+}
+
+pub fn analyze(stats: &TextStats, lexicons: &Lexicons, english: bool) -> Stylometry {
+    let word_count = stats.word_count();
+    if word_count < MIN_WORDS {
+        return Stylometry::default();
+    }
+    let count = |matcher: Option<&Matcher>| matcher.map(|m| m.find(&stats.lower.text).len());
+
+    let lengths: Vec<f64> = stats.words.iter().map(|(_, w)| w.chars().count() as f64).collect();
+    let (mean_word_length, word_length_spread) = mean_and_spread(&lengths);
+
+    let mut out = Stylometry {
+        mean_word_length: Some(mean_word_length),
+        word_length_spread: Some(word_length_spread),
+        function_word_rate: count(lexicons.function_words).map(|n| n as f64 / word_count as f64),
+        contraction_rate: count(lexicons.contractions).map(|n| n as f64 / word_count as f64),
+        ..Stylometry::default()
+    };
+
+    if let (Some(i), Some(you), Some(we)) = (
+        count(lexicons.first_person),
+        count(lexicons.second_person),
+        count(lexicons.first_person_plural),
+    ) {
+        let total = i + you + we;
+        if total >= MIN_PRONOUNS {
+            out.first_person_share = Some(i as f64 / total as f64);
+            out.second_person_share = Some(you as f64 / total as f64);
+            out.first_person_plural_share = Some(we as f64 / total as f64);
+        }
+    }
+
+    if english && !stats.sentences.is_empty() {
+        let syllables: Vec<usize> = stats.lower_words().map(|(_, w)| syllables(w)).collect();
+        let words_per_sentence = word_count as f64 / stats.sentences.len() as f64;
+        let syllables_per_word = syllables.iter().sum::<usize>() as f64 / word_count as f64;
+        let complex = syllables.iter().filter(|&&s| s >= 3).count() as f64 / word_count as f64;
+
+        out.syllables_per_word = Some(syllables_per_word);
+        out.flesch_kincaid = Some(0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59);
+        out.gunning_fog = Some(0.4 * (words_per_sentence + 100.0 * complex));
+    }
+
+    out
+}
+
+fn mean_and_spread(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance.sqrt())
+}
+
+/// English syllable estimate: vowel groups, minus a silent final "e".
+fn syllables(word: &str) -> usize {
+    let letters: Vec<char> = word.chars().filter(char::is_ascii_alphabetic).collect();
+    if letters.is_empty() {
+        return 1; // numbers, symbols: count as one
+    }
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for &c in &letters {
+        let vowel = is_vowel(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    // "make", "hope" — but not "the", "be", or "-le" endings like "table"
+    let n = letters.len();
+    if n > 2 && letters[n - 1] == 'e' && !is_vowel(letters[n - 2]) {
+        let consonant_le = letters[n - 2] == 'l' && !is_vowel(letters[n - 3]);
+        if !consonant_le {
+            count -= 1;
+        }
+    }
+    count.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::rules::Rules;
+
+    fn stylometry(text: &str) -> Stylometry {
+        let rules = Rules::builtin();
+        let lexicon = |name| rules.matcher(Some("en"), name);
+        let lexicons = Lexicons {
+            function_words: lexicon("function_words"),
+            first_person: lexicon("first_person_pronouns"),
+            second_person: lexicon("second_person_pronouns"),
+            first_person_plural: lexicon("first_person_plural_pronouns"),
+            contractions: lexicon("contractions"),
+        };
+        analyze(&TextStats::new(text), &lexicons, true)
+    }
+
+    #[test]
+    fn test_syllables() {
+        for (word, expected) in [("cat", 1), ("make", 1), ("table", 2), ("the", 1), ("comprehensive", 4), ("you", 1), ("42", 1)] {
+            assert_eq!(syllables(word), expected, "{word}");
+        }
+    }
+
+    #[test]
+    fn test_formal_and_casual_profiles() {
+        let formal = stylometry(
+            "Organizations that systematically prioritize comprehensive documentation consistently \
+             demonstrate measurably improved operational efficiency. Furthermore, establishing \
+             standardized communication frameworks facilitates collaboration across geographically \
+             distributed teams. Consequently, leadership should evaluate implementation strategies \
+             carefully, considering organizational readiness and available infrastructure. You \
+             should invest in your team, your processes and your documentation today.",
+        );
+        let casual = stylometry(
+            "ok so I finally got my bike fixed and I'm honestly so happy. I didn't think it'd \
+             take this long but the shop was slammed. I rode it to work today and it's like a \
+             new bike, can't believe I waited so long. I'm gonna ride it all weekend if the \
+             weather's good, I don't care what anyone says lol",
+        );
+
+        assert!(formal.flesch_kincaid.unwrap() > 14.0, "{formal:?}");
+        assert!(formal.mean_word_length.unwrap() > casual.mean_word_length.unwrap());
+        assert!(formal.second_person_share.unwrap() > 0.5, "{formal:?}");
+        assert_eq!(formal.contraction_rate, Some(0.0));
+
+        assert!(casual.flesch_kincaid.unwrap() < 8.0, "{casual:?}");
+        assert!(casual.first_person_share.unwrap() > 0.8, "{casual:?}");
+        assert!(casual.contraction_rate.unwrap() > 0.05, "{casual:?}");
+    }
+
+    #[test]
+    fn test_short_text_not_measured() {
+        let s = stylometry("too short to judge");
+        assert!(s.flesch_kincaid.is_none() && s.mean_word_length.is_none());
+    }
+}
@@ -0,0 +1,348 @@
+//! Normalization and tampering detection, run before every other heuristic.
+//!
+//! "Humanizer" tools disguise AI text so checks like em-dash counting and
+//! lexicon matching miss it: they sprinkle zero-width characters inside words,
+//! swap Latin letters for Cyrillic or Greek lookalikes ("rоbust" with a
+//! Cyrillic о), replace em dashes with lookalike dashes, and pad text with
+//! exotic spaces. This stage records each trick as a finding and produces a
+//! normalized text with the tricks undone, plus a map back to the original
+//! offsets so evidence still points at what the user sees.
+
+use std::ops::Range;
+
+use crate::services::text;
+
+/// Text with invisible characters removed, homoglyphs and confusables folded to
+/// their plain forms, and unusual whitespace turned into spaces.
+pub struct Normalized {
+    pub text: String,
+    /// Original byte range of the char each normalized byte came from.
+    origin: Vec<Range<usize>>,
+    pub findings: Findings,
+}
+
+/// Tampering found in the original text, as byte spans into it.
+#[derive(Debug, Default)]
+pub struct Findings {
+    /// Zero-width and other invisible format characters.
+    pub invisible: Vec<Range<usize>>,
+    /// Words mixing Latin with Cyrillic or Greek letters.
+    pub mixed_script: Vec<Range<usize>>,
+    /// Lookalike dashes and full-width letters or digits in non-CJK text.
+    pub confusables: Vec<Range<usize>>,
+    /// Non-standard space characters.
+    pub whitespace: Vec<Range<usize>>,
+}
+
+impl Normalized {
+    /// Map a byte range in the normalized text back to the original text.
+    pub fn span(&self, span: Range<usize>) -> Range<usize> {
+        if span.is_empty() {
+            let at = self.origin.get(span.start).map_or_else(|| self.source_len(), |r| r.start);
+            return at..at;
+        }
+        self.origin[span.start].start..self.origin[span.end - 1].end
+    }
+
+    /// This text with the byte ranges `cuts` (sorted, non-overlapping) taken out,
+    /// still mapping back to the original. Findings stay with `self`.
+    pub fn without(&self, cuts: &[Range<usize>]) -> Normalized {
+        let mut text = String::with_capacity(self.text.len());
+        let mut origin = Vec::with_capacity(self.origin.len());
+        let mut at = 0;
+        for cut in cuts.iter().chain([&(self.text.len()..self.text.len())]) {
+            text.push_str(&self.text[at..cut.start]);
+            origin.extend_from_slice(&self.origin[at..cut.start]);
+            at = cut.end;
+        }
+        Normalized {
+            text,
+            origin,
+            findings: Findings::default(),
+        }
+    }
+
+    fn source_len(&self) -> usize {
+        self.origin.last().map_or(0, |r| r.end)
+    }
+}
+
+pub fn normalize(text: &str) -> Normalized {
+    let cjk = text.chars().any(text::is_cjk);
+    let mixed_words = mixed_script_words(text);
+    let mut findings = Findings {
+        mixed_script: mixed_words.clone(),
+        ..Findings::default()
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut origin = Vec::with_capacity(text.len());
+    let mut words = mixed_words.iter().peekable();
+
+    for (i, ch) in text.char_indices() {
+        let span = i..i + ch.len_utf8();
+        let context = Context {
+            before: &text[..i],
+            after: &text[span.end..],
+        };
+        while words.peek().is_some_and(|w| w.end <= i) {
+            words.next();
+        }
+        let in_mixed_word = words.peek().is_some_and(|w| w.start <= i);
+
+        let replacement = if is_invisible(ch, &context) {
+            findings.invisible.push(span.clone());
+            None
+        } else if let Some(latin) = in_mixed_word.then(|| homoglyph(ch)).flatten() {
+            Some(latin)
+        } else if let Some(plain) = confusable(ch, cjk) {
+            findings.confusables.push(span.clone());
+            Some(plain)
+        } else if is_unusual_space(ch, &context) {
+            findings.whitespace.push(span.clone());
+            Some(' ')
+        } else {
+            Some(ch)
+        };
+
+        if let Some(c) = replacement {
+            out.push(c);
+            origin.extend(std::iter::repeat_n(span.clone(), c.len_utf8()));
+        }
+    }
+
+    Normalized {
+        text: out,
+        origin,
+        findings,
+    }
+}
+
+/// Spans of words (runs of letters, digits and invisible characters) that mix
+/// Latin letters with Cyrillic or Greek ones.
+fn mixed_script_words(text: &str) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut start = None;
+    let (mut latin, mut other) = (false, false);
+
+    for (i, ch) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        if ch.is_alphanumeric() || is_invisible(ch, &Context::default()) {
+            start.get_or_insert(i);
+            latin |= is_latin(ch);
+            other |= is_cyrillic_or_greek(ch);
+        } else if let Some(s) = start.take() {
+            if latin && other {
+                out.push(s..i);
+            }
+            (latin, other) = (false, false);
+        }
+    }
+    out
+}
+
+/// Text around a character.
+#[derive(Default)]
+struct Context<'a> {
+    before: &'a str,
+    after: &'a str,
+}
+
+/// Chars looked through for the nearest letter around a direction mark.
+const MARK_REACH: usize = 16;
+
+impl Context<'_> {
+    fn prev(&self) -> Option<char> {
+        self.before.chars().next_back()
+    }
+
+    fn next(&self) -> Option<char> {
+        self.after.chars().next()
+    }
+
+    /// Whether the nearest letter on either side, within `MARK_REACH` chars, is in a right-to-left script.
+    fn near_rtl(&self) -> bool {
+        let before = self.before.chars().rev().take(MARK_REACH).find(|c| c.is_alphabetic());
+        let after = self.after.chars().take(MARK_REACH).find(|c| c.is_alphabetic());
+        before.is_some_and(is_rtl) || after.is_some_and(is_rtl)
+    }
+}
+
+/// Zero-width and format characters. Some are ordinary spelling rather than
+/// tampering: a zero-width joiner between two emoji is part of an emoji
+/// sequence (👩‍💻), the zero-width (non-)joiner shapes Arabic-script and Indic
+/// words (Persian می‌خواهم), direction marks sit next to Hebrew and Arabic, and a
+/// soft hyphen between two letters is a hyphenation point (German
+/// Donau\u{00AD}dampfschiff).
+fn is_invisible(c: char, context: &Context) -> bool {
+    let (prev, next) = (context.prev(), context.next());
+    match c {
+        '\u{200C}' | '\u{200D}' if prev.is_some_and(joins) || next.is_some_and(joins) => return false,
+        '\u{200D}' => return !(prev.is_some_and(text::is_emoji) && next.is_some_and(text::is_emoji)),
+        '\u{200E}' | '\u{200F}' if context.near_rtl() => return false,
+        '\u{00AD}' if prev.is_some_and(char::is_alphabetic) && next.is_some_and(char::is_alphabetic) => return false,
+        _ => {}
+    }
+    matches!(c,
+        '\u{00AD}'                    // soft hyphen
+        | '\u{034F}'                  // combining grapheme joiner
+        | '\u{115F}' | '\u{1160}' | '\u{3164}' | '\u{FFA0}' // Hangul fillers
+        | '\u{180E}'                  // Mongolian vowel separator
+        | '\u{200B}'..='\u{200F}'     // zero-width space/non-joiner, direction marks
+        | '\u{202A}'..='\u{202E}'     // bidi embeddings and overrides
+        | '\u{2060}'..='\u{2064}'     // word joiner, invisible operators
+        | '\u{2066}'..='\u{2069}'     // bidi isolates
+        | '\u{FEFF}'                  // zero-width no-break space
+    )
+}
+
+/// Space characters other than the plain space, tab and line breaks. A no-break
+/// space before `;:!?»` or after `«` is French typography, not tampering, and the
+/// ideographic space is normal in CJK text.
+fn is_unusual_space(c: char, context: &Context) -> bool {
+    match c {
+        '\u{00A0}' | '\u{202F}' => {
+            !(context.next().is_some_and(|n| matches!(n, ';' | ':' | '!' | '?' | '»')) || context.prev() == Some('«'))
+        }
+        '\u{1680}' | '\u{2000}'..='\u{200A}' | '\u{205F}' => true,
+        _ => false,
+    }
+}
+
+/// Plain form of a lookalike dash, or of a full-width letter or digit in text
+/// without CJK (where full-width forms are ordinary). Dashes with a typographic
+/// use of their own (hyphen, non-breaking hyphen, figure dash, minus sign,
+/// two- and three-em dashes) are left alone.
+fn confusable(c: char, cjk: bool) -> Option<char> {
+    match c {
+        // Horizontal bar, small and vertical em dashes
+        '\u{2015}' | '\u{FE58}' | '\u{FE31}' | '\u{FE32}' => Some('—'),
+        // Small and full-width hyphen-minus
+        '\u{FE63}' | '\u{FF0D}' => Some('-'),
+        '\u{FF10}'..='\u{FF19}' | '\u{FF21}'..='\u{FF3A}' | '\u{FF41}'..='\u{FF5A}' if !cjk => {
+            char::from_u32(c as u32 - 0xFEE0)
+        }
+        _ => None,
+    }
+}
+
+/// Latin lookalike of a Cyrillic or Greek letter.
+fn homoglyph(c: char) -> Option<char> {
+    let latin = match c {
+        // Cyrillic
+        'а' => 'a', 'в' => 'b', 'е' => 'e', 'к' => 'k', 'м' => 'm', 'н' => 'h', 'о' => 'o',
+        'р' => 'p', 'с' => 'c', 'т' => 't', 'у' => 'y', 'х' => 'x', 'ѕ' => 's', 'і' => 'i',
+        'ј' => 'j', 'ԁ' => 'd', 'ӏ' => 'l', 'һ' => 'h', 'ԛ' => 'q', 'ԝ' => 'w', 'ү' => 'y',
+        'А' => 'A', 'В' => 'B', 'Е' => 'E', 'К' => 'K', 'М' => 'M', 'Н' => 'H', 'О' => 'O',
+        'Р' => 'P', 'С' => 'C', 'Т' => 'T', 'Х' => 'X', 'Ү' => 'Y', 'Ѕ' => 'S', 'І' => 'I',
+        'Ј' => 'J', 'Ԛ' => 'Q', 'Ԝ' => 'W',
+        // Greek
+        'α' => 'a', 'ε' => 'e', 'ι' => 'i', 'κ' => 'k', 'ν' => 'v', 'ο' => 'o', 'ρ' => 'p',
+        'τ' => 't', 'υ' => 'u', 'χ' => 'x', 'Α' => 'A', 'Β' => 'B', 'Ε' => 'E', 'Ζ' => 'Z',
+        'Η' => 'H', 'Ι' => 'I', 'Κ' => 'K', 'Μ' => 'M', 'Ν' => 'N', 'Ο' => 'O', 'Ρ' => 'P',
+        'Τ' => 'T', 'Υ' => 'Y', 'Χ' => 'X',
+        _ => return None,
+    };
+    Some(latin)
+}
+
+fn is_latin(c: char) -> bool {
+    c.is_ascii_alphabetic() || matches!(c, '\u{00C0}'..='\u{024F}' if c.is_alphabetic())
+}
+
+fn is_cyrillic_or_greek(c: char) -> bool {
+    matches!(c, '\u{0370}'..='\u{03FF}' | '\u{0400}'..='\u{052F}')
+}
+
+/// Letters and marks of scripts spelled with zero-width (non-)joiners: Arabic,
+/// Syriac, Thaana, N'Ko, the Indic scripts, Tibetan, Myanmar, Khmer and Mongolian.
+fn joins(c: char) -> bool {
+    matches!(c,
+        '\u{0600}'..='\u{07FF}'
+        | '\u{08A0}'..='\u{08FF}'
+        | '\u{0900}'..='\u{0DFF}'
+        | '\u{0F00}'..='\u{0FFF}'
+        | '\u{1000}'..='\u{109F}'
+        | '\u{1780}'..='\u{18AF}'
+        | '\u{A8E0}'..='\u{A8FF}'
+        | '\u{FB50}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFC}'
+    )
+}
+
+/// Letters of right-to-left scripts: Hebrew, Arabic, Syriac, Thaana and N'Ko.
+fn is_rtl(c: char) -> bool {
+    matches!(c,
+        '\u{0590}'..='\u{07FF}'
+        | '\u{08A0}'..='\u{08FF}'
+        | '\u{FB1D}'..='\u{FDFF}'
+        | '\u{FE70}'..='\u{FEFC}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invisible_characters_removed() {
+        let text = "ro\u{200B}bust\u{00AD} game\u{200C}changer 👩\u{200D}💻";
+        let n = normalize(text);
+        assert_eq!(n.text, "robust gamechanger 👩\u{200D}💻");
+        assert_eq!(n.findings.invisible.len(), 3);
+        assert_eq!(&text[n.span(0..6)], "ro\u{200B}bust");
+    }
+
+    #[test]
+    fn test_spelling_joiners_and_marks_kept() {
+        // Persian and Hindi joiners, a right-to-left mark after Hebrew, a German hyphenation point
+        for text in [
+            "می\u{200C}خواهم با بچه\u{200C}ها بروم",
+            "क्\u{200D}ष और क्\u{200C}ष",
+            "שלום\u{200F} (2024)",
+            "Donau\u{00AD}dampf\u{00AD}schiff",
+        ] {
+            let n = normalize(text);
+            assert_eq!(n.text, text);
+            assert!(n.findings.invisible.is_empty(), "{text}");
+        }
+        // The same characters between Latin letters are still tampering
+        assert_eq!(normalize("ro\u{200D}bust\u{200E} text").findings.invisible.len(), 2);
+    }
+
+    #[test]
+    fn test_homoglyphs_folded_in_mixed_words_only() {
+        // Cyrillic о and е inside Latin words; a genuine Russian word is left alone
+        let text = "a rоbust framеwork, привет";
+        let n = normalize(text);
+        assert_eq!(n.text, "a robust framework, привет");
+        assert_eq!(n.findings.mixed_script.len(), 2);
+        assert_eq!(&text[n.findings.mixed_script[0].clone()], "rоbust");
+    }
+
+    #[test]
+    fn test_confusables_and_whitespace() {
+        let text = "fast\u{2015}really\u{2003}fast ＡＩ";
+        let n = normalize(text);
+        assert_eq!(n.text, "fast—really fast AI");
+        assert_eq!(n.findings.confusables.len(), 3);
+        assert_eq!(n.findings.whitespace.len(), 1);
+
+        // Full-width forms are ordinary in CJK text, French no-break spaces are typography
+        assert!(normalize("ＡＩの時代").findings.confusables.is_empty());
+        assert!(normalize("Vraiment\u{00A0}?").findings.whitespace.is_empty());
+        // Typographic minus signs, hyphens and figure dashes are not lookalikes
+        let text = "Down to \u{2212}5°C, a non\u{2011}stop 555\u{2012}0100 line";
+        let n = normalize(text);
+        assert_eq!(n.text, text);
+        assert!(n.findings.confusables.is_empty());
+    }
+
+    #[test]
+    fn test_clean_text_unchanged() {
+        let text = "Just a normal post — with “quotes” and café.";
+        let n = normalize(text);
+        assert_eq!(n.text, text);
+        assert!(n.findings.invisible.is_empty() && n.findings.confusables.is_empty());
+        assert_eq!(n.span(7..13), 7..13);
+    }
+}
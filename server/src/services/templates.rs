@@ -0,0 +1,290 @@
+//! Near-duplicate and template detection across stored posts.
+//!
+//! Every post gets a MinHash signature of its word 3-shingles, taken after
+//! tampering normalization with links, mentions and hashtags cut: 64 hash
+//! functions, each keeping its smallest shingle hash. The share of positions
+//! where two signatures agree estimates the Jaccard similarity of their
+//! shingle sets, so the same template with a few words swapped stays close.
+//! Signatures are indexed by locality-sensitive bands (16 bands of 4 hashes):
+//! posts sharing any band are candidates, which is all but certain from a
+//! similarity of about 0.7 up, and the signatures then confirm the match.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::models::{Fingerprint, NearDuplicate, NearDuplicates, TemplateCluster};
+use crate::services::{entities, tampering, text};
+
+/// Other accounts that posted near-copies, registered in `rules::MEASUREMENTS`.
+pub const MEASUREMENTS: &[&str] = &["template_reuse"];
+
+/// Hash functions per signature.
+const HASHES: usize = 64;
+
+/// Locality-sensitive bands the signature is indexed by, of `HASHES / BANDS` hashes each.
+pub const BANDS: usize = 16;
+
+/// Words per shingle.
+const SHINGLE: usize = 3;
+
+/// Shingles needed for a signature; shorter posts (stock replies) match by chance.
+const MIN_SHINGLES: usize = 8;
+
+/// Newest band matches checked per post.
+pub const MAX_CANDIDATES: i64 = 1000;
+
+/// Closest matches reported per post.
+const CLOSEST: usize = 5;
+
+/// Posts listed per template cluster.
+const CLUSTER_POSTS: usize = 20;
+
+/// 64-bit FNV-1a. Stored signatures must hash the same across builds, which
+/// the standard library hasher doesn't promise.
+fn fnv1a(words: &[&str]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for word in words {
+        for byte in word.bytes().chain([b' ']) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// SplitMix64 finalizer: the `seed`-th hash function of a shingle hash.
+fn mix(hash: u64, seed: u64) -> u64 {
+    let mut z = hash ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// MinHash signature of the post's word shingles, None when it is too short.
+pub fn signature(content: &str) -> Option<Vec<u32>> {
+    let text = entities::strip(&tampering::normalize(content).text).to_lowercase();
+    let words: Vec<&str> = text::word_indices(&text).into_iter().map(|(_, w)| w).collect();
+    let shingles: BTreeSet<u64> = words.windows(SHINGLE).map(fnv1a).collect();
+    if shingles.len() < MIN_SHINGLES {
+        return None;
+    }
+    Some(
+        (0..HASHES as u64)
+            .map(|seed| shingles.iter().map(|&s| (mix(s, seed) >> 32) as u32).min().expect("shingles is not empty"))
+            .collect(),
+    )
+}
+
+/// Little-endian bytes of a signature, as stored.
+pub fn to_bytes(signature: &[u32]) -> Vec<u8> {
+    signature.iter().flat_map(|h| h.to_le_bytes()).collect()
+}
+
+pub fn from_bytes(bytes: &[u8]) -> Vec<u32> {
+    bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+/// The signature's band keys, as stored.
+pub fn bands(signature: &[u32]) -> Vec<i64> {
+    signature
+        .chunks(HASHES / BANDS)
+        .map(|rows| {
+            let mut hash = 0xcbf2_9ce4_8422_2325u64;
+            for byte in rows.iter().flat_map(|h| h.to_le_bytes()) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+            hash as i64
+        })
+        .collect()
+}
+
+/// Estimated Jaccard similarity of two signatures: the share of hashes they agree on.
+pub fn similarity(a: &[u32], b: &[u32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    a.iter().zip(b).filter(|(x, y)| x == y).count() as f64 / a.len() as f64
+}
+
+impl Fingerprint {
+    fn near(&self, similarity: f64) -> NearDuplicate {
+        NearDuplicate {
+            id: self.id.clone(),
+            author: self.author.clone(),
+            platform: self.platform.clone(),
+            score: self.score,
+            similarity,
+            content_preview: self.content_preview.clone(),
+            created_at: self.created_at.clone(),
+        }
+    }
+}
+
+/// The author as an account name; None for anonymous posts.
+fn account(author: &Option<String>) -> Option<&str> {
+    author.as_deref().filter(|a| !a.is_empty() && *a != "unknown")
+}
+
+/// The `candidates` at least `min_similarity` similar to `signature`, and how
+/// many other accounts posted them: distinct authors other than `author`, plus
+/// every anonymous post.
+pub fn near_duplicates(
+    signature: &[u32],
+    author: Option<&str>,
+    candidates: &[Fingerprint],
+    min_similarity: f64,
+) -> (NearDuplicates, usize) {
+    let mut matches: Vec<NearDuplicate> = candidates
+        .iter()
+        .filter_map(|c| {
+            let s = similarity(signature, &from_bytes(&c.minhash));
+            (s >= min_similarity).then(|| c.near(s))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then_with(|| b.created_at.cmp(&a.created_at)));
+
+    let authors: BTreeSet<&str> = matches.iter().filter_map(|m| account(&m.author)).collect();
+    let anonymous = matches.iter().filter(|m| account(&m.author).is_none()).count();
+    let reuse = authors.iter().filter(|a| Some(**a) != author).count() + anonymous;
+    let result = NearDuplicates {
+        count: matches.len(),
+        authors: authors.into_iter().map(str::to_string).collect(),
+        closest: matches.into_iter().take(CLOSEST).collect(),
+    };
+    (result, reuse)
+}
+
+/// Union-find root of `i`, halving the path on the way.
+pub(crate) fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Groups of `posts` linked by signatures at least `min_similarity` similar,
+/// with at least `min_size` posts, largest first.
+pub fn clusters(posts: &[Fingerprint], min_similarity: f64, min_size: usize) -> Vec<TemplateCluster> {
+    let signatures: Vec<Vec<u32>> = posts.iter().map(|p| from_bytes(&p.minhash)).collect();
+    let mut parent: Vec<usize> = (0..posts.len()).collect();
+
+    // Only posts sharing a band are compared
+    let mut buckets: HashMap<(usize, i64), Vec<usize>> = HashMap::new();
+    for (i, signature) in signatures.iter().enumerate() {
+        for (band, key) in bands(signature).into_iter().enumerate() {
+            buckets.entry((band, key)).or_default().push(i);
+        }
+    }
+    for members in buckets.values() {
+        for (n, &i) in members.iter().enumerate() {
+            for &j in &members[n + 1..] {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                if a != b && similarity(&signatures[i], &signatures[j]) >= min_similarity {
+                    parent[a] = b;
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..posts.len() {
+        groups.entry(root(&mut parent, i)).or_default().push(i);
+    }
+    let mut out: Vec<TemplateCluster> = groups
+        .into_values()
+        .filter(|group| group.len() >= min_size)
+        .map(|mut group| {
+            group.sort_by(|&a, &b| posts[b].created_at.cmp(&posts[a].created_at));
+            let newest = &signatures[group[0]];
+            let authors: BTreeSet<&str> = group.iter().filter_map(|&i| account(&posts[i].author)).collect();
+            TemplateCluster {
+                size: group.len(),
+                authors: authors.into_iter().map(str::to_string).collect(),
+                first_seen: posts[group[group.len() - 1]].created_at.clone(),
+                last_seen: posts[group[0]].created_at.clone(),
+                posts: group
+                    .iter()
+                    .take(CLUSTER_POSTS)
+                    .map(|&i| posts[i].near(similarity(newest, &signatures[i])))
+                    .collect(),
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| b.last_seen.cmp(&a.last_seen)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "Excited to announce that I have joined Acme as a senior engineer! \
+                            Grateful for the journey so far and can't wait to build the future \
+                            of payments with this incredible team. #NewBeginnings";
+
+    const OTHER: &str = "Spent the weekend fixing the fence with my dad. Took three trips to the \
+                         hardware store and we still ended up one board short, classic.";
+
+    fn post(id: &str, author: Option<&str>, content: &str, created_at: &str) -> Fingerprint {
+        Fingerprint {
+            id: id.to_string(),
+            author: author.map(str::to_string),
+            platform: "linkedin".to_string(),
+            score: 7,
+            minhash: to_bytes(&signature(content).unwrap()),
+            content_preview: content.chars().take(150).collect(),
+            created_at: created_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_signature_similarity() {
+        let edited = TEMPLATE.replace("Acme", "Globex").replace("#NewBeginnings", "https://example.com");
+        let a = signature(TEMPLATE).unwrap();
+        let b = signature(&edited).unwrap();
+        assert_eq!(from_bytes(&to_bytes(&a)), a);
+        assert!(similarity(&a, &b) >= 0.7, "{}", similarity(&a, &b));
+        assert!(similarity(&a, &signature(OTHER).unwrap()) < 0.2);
+        assert!(signature("Great post, thanks for sharing!").is_none());
+        // Near-duplicates share a band
+        assert_eq!(bands(&a).len(), BANDS);
+        assert!(bands(&a).iter().zip(bands(&b)).any(|(x, y)| *x == y));
+    }
+
+    #[test]
+    fn test_near_duplicates() {
+        let edited = TEMPLATE.replace("Acme", "Globex");
+        let candidates = [
+            post("1", Some("alice"), &edited, "2026-01-02"),
+            post("2", Some("bob"), TEMPLATE, "2026-01-01"),
+            post("3", Some("carol"), TEMPLATE, "2026-01-03"),
+            post("4", None, TEMPLATE, "2026-01-04"),
+            post("5", Some("dave"), OTHER, "2026-01-05"),
+        ];
+        let (found, reuse) = near_duplicates(&signature(TEMPLATE).unwrap(), Some("bob"), &candidates, 0.7);
+        assert_eq!(found.count, 4);
+        assert_eq!(found.authors, ["alice", "bob", "carol"]);
+        // Exact copies first, newest first
+        assert_eq!(found.closest.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(), ["4", "3", "2", "1"]);
+        assert_eq!(reuse, 3); // alice, carol and the anonymous post
+    }
+
+    #[test]
+    fn test_clusters() {
+        let edited = TEMPLATE.replace("senior engineer", "product manager");
+        let posts = [
+            post("1", Some("alice"), TEMPLATE, "2026-01-01"),
+            post("2", Some("bob"), &edited, "2026-01-02"),
+            post("3", Some("carol"), TEMPLATE, "2026-01-03"),
+            post("4", Some("dave"), OTHER, "2026-01-04"),
+        ];
+        let found = clusters(&posts, 0.7, 2);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].size, 3);
+        assert_eq!(found[0].authors, ["alice", "bob", "carol"]);
+        assert_eq!((found[0].first_seen.as_str(), found[0].last_seen.as_str()), ("2026-01-01", "2026-01-03"));
+        assert_eq!(found[0].posts[0].similarity, 1.0);
+        assert!(clusters(&posts, 0.7, 4).is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+//! Tokenization layer shared by every heuristic.
+//!
+//! Words follow Unicode word boundaries (UAX #29), so CJK ideographs and kana
+//! come out as one token each instead of one giant whitespace-free "word".
+//! Scripts that need a dictionary to find word breaks (Thai, Lao, Khmer,
+//! Myanmar) fall back to one token per grapheme cluster. Sentence splitting
+//! lives in the segmenter.
+
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Sentence-ending punctuation, including full-width CJK forms.
+pub const TERMINATORS: &[char] = &[
+    '.', '!', '?', '…', '。', '｡', '！', '？', '‼', '⁇', '⁈', '⁉', '؟', '।', '॥',
+];
+
+/// Period-like terminators (as opposed to `!` / `?` forms).
+pub const PERIODS: &[char] = &['.', '。', '｡', '।', '॥'];
+
+pub fn is_terminator(c: char) -> bool {
+    TERMINATORS.contains(&c)
+}
+
+/// Word tokens with their byte offsets into `text`.
+pub fn word_indices(text: &str) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    for (i, word) in text.unicode_word_indices() {
+        if word.chars().any(is_unsegmented_script) {
+            out.extend(word.grapheme_indices(true).map(|(j, g)| (i + j, g)));
+        } else {
+            out.push((i, word));
+        }
+    }
+    out
+}
+
+pub fn word_count(text: &str) -> usize {
+    word_indices(text).len()
+}
+
+/// Byte ranges of non-empty lines, trimmed.
+pub fn line_spans(text: &str) -> Vec<Range<usize>> {
+    line_spans_with_blanks(text).into_iter().flatten().collect()
+}
+
+/// Trimmed line spans, with None for blank lines.
+pub fn line_spans_with_blanks(text: &str) -> Vec<Option<Range<usize>>> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    for line in text.split('\n') {
+        let trimmed = line.trim();
+        out.push((!trimmed.is_empty()).then(|| {
+            let offset = start + (line.len() - line.trim_start().len());
+            offset..offset + trimmed.len()
+        }));
+        start += line.len() + 1;
+    }
+    out
+}
+
+/// Pictographic emoji, regional indicators and the joiners that glue them together.
+pub fn is_emoji(c: char) -> bool {
+    matches!(c,
+        '\u{1F000}'..='\u{1FAFF}'   // pictographs, emoticons, flags, symbols
+        | '\u{2600}'..='\u{27BF}'   // misc symbols, dingbats
+        | '\u{2B00}'..='\u{2BFF}'   // stars, arrows
+        | '\u{FE0F}' | '\u{200D}' | '\u{20E3}'
+    )
+}
+
+/// Letters and digits of space-delimited scripts, i.e. characters that must not
+/// touch a lexicon match for it to count as a whole word. CJK and Thai-like
+/// scripts have no spaces, so their matches need no boundary.
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() && !is_unsegmented_script(c) && !is_cjk(c)
+}
+
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'   // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+        | '\u{FF66}'..='\u{FF9F}' // Half-width Katakana
+    )
+}
+
+/// Scripts written without spaces that UAX #29 leaves as one run.
+fn is_unsegmented_script(c: char) -> bool {
+    matches!(c,
+        '\u{0E00}'..='\u{0E7F}'   // Thai
+        | '\u{0E80}'..='\u{0EFF}' // Lao
+        | '\u{1000}'..='\u{109F}' // Myanmar
+        | '\u{1780}'..='\u{17FF}' // Khmer
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cjk_words() {
+        let text = "今日はとても良い天気です。散歩に行きました！楽しかった？";
+        assert!(word_count(text) > 10);
+    }
+
+    #[test]
+    fn test_thai_falls_back_to_graphemes() {
+        let text = "วันนี้อากาศดีมาก";
+        assert!(word_count(text) > 5);
+    }
+
+    #[test]
+    fn test_latin_words_keep_offsets() {
+        let text = "Don't stop, y'all — 3.5x faster.";
+        let words = word_indices(text);
+        assert_eq!(words[0], (0, "Don't"));
+        assert!(words.iter().any(|(_, w)| *w == "y'all"));
+        assert!(words.iter().any(|(_, w)| *w == "3.5x"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Local transformer classifier, run on CPU (`transformer` cargo feature).
+//!
+//! Loads a RoBERTa-style sequence classifier fine-tuned to tell AI from human
+//! text (e.g. `roberta-base-openai-detector`) from a directory holding the
+//! Hugging Face `config.json`, `tokenizer.json` and `model.safetensors`, and
+//! runs it with candle inside `spawn_blocking`, like the heuristics. Nothing
+//! goes over the network, so it stands in for an LLM provider where there is
+//! no API budget. Posts longer than the model's context are split into
+//! overlapping chunks whose AI probabilities are averaged, weighted by length.
+
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::xlm_roberta::{Config as ModelConfig, XLMRobertaForSequenceClassification};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tokenizers::Tokenizer;
+
+use crate::errors::AppError;
+use crate::services::ensemble::{Detector, Input, Verdict};
+
+/// Tokens shared by consecutive chunks, so no sentence is only ever seen cut in half.
+const CHUNK_OVERLAP: usize = 64;
+
+/// Chunks scored per post; later text is ignored (about 7,000 tokens at RoBERTa size).
+const MAX_CHUNKS: usize = 16;
+
+/// Label names (lowercased) that mean AI-written. Without one, label 1 is taken.
+const AI_LABELS: &[&str] = &["fake", "ai", "machine", "generated", "ai-generated", "machine-generated", "chatgpt", "gpt"];
+
+/// Labels from `config.json`.
+#[derive(Deserialize)]
+struct Labels {
+    #[serde(default)]
+    id2label: BTreeMap<String, String>,
+}
+
+struct Model {
+    classifier: XLMRobertaForSequenceClassification,
+    tokenizer: Tokenizer,
+    /// Token ids wrapped around every chunk.
+    bos: u32,
+    eos: u32,
+    /// Content tokens per chunk, leaving room for `bos` and `eos`.
+    chunk: usize,
+    num_labels: usize,
+    /// Output index of the AI label.
+    ai_label: usize,
+}
+
+/// A transformer classifier as an ensemble detector.
+pub struct Transformer {
+    model: Arc<Model>,
+}
+
+impl Transformer {
+    /// Load the model in `dir` (`config.json`, `tokenizer.json`, `model.safetensors`).
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let read = |name: &str| {
+            fs::read_to_string(dir.join(name)).map_err(|e| format!("Failed to read {}: {e}", dir.join(name).display()))
+        };
+        let config_json = read("config.json")?;
+
+        // Older RoBERTa configs leave out fields that later versions default
+        let mut config: serde_json::Value =
+            serde_json::from_str(&config_json).map_err(|e| format!("Invalid config.json: {e}"))?;
+        let object = config.as_object_mut().ok_or("config.json must be an object")?;
+        for (key, default) in [
+            ("position_embedding_type", serde_json::json!("absolute")),
+            ("type_vocab_size", serde_json::json!(1)),
+            ("pad_token_id", serde_json::json!(1)),
+            ("attention_probs_dropout_prob", serde_json::json!(0.1)),
+            ("hidden_dropout_prob", serde_json::json!(0.1)),
+        ] {
+            object.entry(key).or_insert(default);
+        }
+        let model_config: ModelConfig =
+            serde_json::from_value(config.clone()).map_err(|e| format!("Unsupported config.json: {e}"))?;
+        let labels: Labels = serde_json::from_value(config).map_err(|e| format!("Invalid id2label: {e}"))?;
+        let num_labels = labels.id2label.len().max(2);
+        let ai_label = ai_label(&labels.id2label);
+
+        let tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| format!("Failed to load tokenizer.json: {e}"))?;
+        let token = |names: &[&str]| names.iter().find_map(|name| tokenizer.token_to_id(name));
+        let bos = token(&["<s>", "[CLS]"]).ok_or("Tokenizer has no <s> or [CLS] token")?;
+        let eos = token(&["</s>", "[SEP]"]).ok_or("Tokenizer has no </s> or [SEP] token")?;
+
+        let weights = dir.join("model.safetensors");
+        // SAFETY: the weights file is memory-mapped and must not change while the server runs.
+        let vb = unsafe { VarBuilder::from_mmaped_safetensors(&[&weights], DType::F32, &Device::Cpu) }
+            .map_err(|e| format!("Failed to load {}: {e}", weights.display()))?;
+        let classifier = XLMRobertaForSequenceClassification::new(num_labels, &model_config, vb)
+            .map_err(|e| format!("Failed to build the model from {}: {e}", weights.display()))?;
+
+        // RoBERTa positions start after the padding index
+        let positions = model_config.max_position_embeddings - model_config.pad_token_id as usize - 1;
+        Ok(Self {
+            model: Arc::new(Model {
+                classifier,
+                tokenizer,
+                bos,
+                eos,
+                chunk: positions.saturating_sub(2).max(CHUNK_OVERLAP + 1),
+                num_labels,
+                ai_label,
+            }),
+        })
+    }
+}
+
+/// Index of the AI label in `id2label`, else 1.
+fn ai_label(id2label: &BTreeMap<String, String>) -> usize {
+    id2label
+        .iter()
+        .find(|(_, label)| AI_LABELS.contains(&label.to_lowercase().as_str()))
+        .and_then(|(id, _)| id.parse().ok())
+        .unwrap_or(1)
+}
+
+/// Overlapping windows of at most `size` tokens covering `len` tokens, at most `MAX_CHUNKS`.
+fn chunks(len: usize, size: usize) -> Vec<std::ops::Range<usize>> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    loop {
+        out.push(start..len.min(start + size));
+        if start + size >= len || out.len() == MAX_CHUNKS {
+            return out;
+        }
+        start += size - CHUNK_OVERLAP;
+    }
+}
+
+impl Model {
+    /// Probability that `text` is AI-written, averaged over its chunks; 0.5 for no text.
+    fn probability(&self, text: &str) -> Result<f64, String> {
+        let encoding = self.tokenizer.encode(text, false).map_err(|e| format!("Tokenization failed: {e}"))?;
+        let ids = encoding.get_ids();
+        if ids.is_empty() {
+            // Nothing left to read (e.g. only links): no opinion
+            return Ok(0.5);
+        }
+        let (mut sum, mut tokens) = (0.0, 0.0);
+        for range in chunks(ids.len(), self.chunk) {
+            let len = range.len() as f64;
+            let input: Vec<u32> = [self.bos].into_iter().chain(ids[range].iter().copied()).chain([self.eos]).collect();
+            sum += self.chunk_probability(&input).map_err(|e| format!("Inference failed: {e}"))? * len;
+            tokens += len;
+        }
+        Ok(sum / tokens)
+    }
+
+    fn chunk_probability(&self, ids: &[u32]) -> candle_core::Result<f64> {
+        let input_ids = Tensor::new(ids, &Device::Cpu)?.unsqueeze(0)?;
+        let attention_mask = input_ids.ones_like()?;
+        let token_type_ids = input_ids.zeros_like()?;
+        let logits = self.classifier.forward(&input_ids, &attention_mask, &token_type_ids)?;
+        let probabilities = candle_nn::ops::softmax_last_dim(&logits)?.squeeze(0)?.to_vec1::<f32>()?;
+        Ok(probabilities.get(self.ai_label.min(self.num_labels - 1)).copied().unwrap_or(0.0) as f64)
+    }
+}
+
+#[async_trait]
+impl Detector for Transformer {
+    fn name(&self) -> &'static str {
+        "transformer"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        let text = input.stripped();
+        let model = self.model.clone();
+        let p = tokio::task::spawn_blocking(move || model.probability(&text))
+            .await
+            .map_err(|e| AppError::Internal(format!("Transformer inference panicked: {e}")))?
+            .map_err(AppError::Internal)?;
+        Ok(Verdict::probability(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_overlap_and_cover() {
+        assert!(chunks(0, 510)[0].is_empty());
+        assert_eq!(chunks(300, 510).len(), 1);
+        assert_eq!(chunks(300, 510)[0], 0..300);
+        assert_eq!(chunks(1000, 510), vec![0..510, 446..956, 892..1000]);
+        assert_eq!(chunks(1_000_000, 510).len(), MAX_CHUNKS);
+    }
+
+    #[test]
+    fn test_ai_label() {
+        let labels = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(ai_label(&labels(&[("0", "Fake"), ("1", "Real")])), 0);
+        assert_eq!(ai_label(&labels(&[("0", "human"), ("1", "machine")])), 1);
+        assert_eq!(ai_label(&labels(&[("0", "LABEL_0"), ("1", "LABEL_1")])), 1);
+    }
+}
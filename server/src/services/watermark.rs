@@ -0,0 +1,259 @@
+//! Green-list watermark detection (`watermark` cargo feature).
+//!
+//! Watermarking generators (Kirchenbauer et al., 2023) split the vocabulary
+//! at every step into a "green" share `gamma` chosen by a keyed hash of the
+//! previous tokens, then nudge sampling toward green tokens. Text from such a
+//! generator holds far more green tokens than the `gamma` share chance
+//! predicts, which a one-proportion z-test finds without the model. Each known
+//! scheme (key, `gamma`, context width and the generator's `tokenizer.json`)
+//! is listed in the file at `WATERMARK_PATH`:
+//!
+//! ```toml
+//! [[schemes]]
+//! name = "acme"
+//! key = 15485863
+//! gamma = 0.25
+//! context = 1
+//! tokenizer = "acme-tokenizer.json"   # relative to this file
+//! ```
+//!
+//! A token is green when the first 8 bytes of SHA-256 over the little-endian
+//! key, context token ids and token id, read as a fraction of 2^64, fall
+//! below `gamma`. Generators must use the same construction. Repeated
+//! (context, token) pairs are tested once, so repetitive text can't inflate
+//! the count.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokenizers::Tokenizer;
+
+use crate::errors::AppError;
+use crate::models::WatermarkResult;
+use crate::services::ensemble::{Detector, Input, Verdict};
+use crate::services::rules::Rules;
+use crate::services::tampering;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemesFile {
+    schemes: Vec<SchemeConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SchemeConfig {
+    name: String,
+    key: u64,
+    /// Green share of the vocabulary, 0-1 exclusive.
+    gamma: f64,
+    /// Previous tokens hashed into the green list.
+    #[serde(default = "default_context")]
+    context: usize,
+    tokenizer: PathBuf,
+}
+
+fn default_context() -> usize {
+    1
+}
+
+struct Scheme {
+    name: String,
+    key: u64,
+    gamma: f64,
+    context: usize,
+    tokenizer: Tokenizer,
+}
+
+/// Whether `token` is on the green list after `context` under `key`.
+pub fn is_green(key: u64, context: &[u32], token: u32, gamma: f64) -> bool {
+    let mut hasher = Sha256::new();
+    hasher.update(key.to_le_bytes());
+    for id in context {
+        hasher.update(id.to_le_bytes());
+    }
+    hasher.update(token.to_le_bytes());
+    let digest = hasher.finalize();
+    let value = u64::from_le_bytes(digest[..8].try_into().expect("SHA-256 is 32 bytes"));
+    (value as f64 / u64::MAX as f64) < gamma
+}
+
+/// (tested, green) tokens of `ids`: every token with a full context, each
+/// distinct (context, token) pair once.
+pub fn count_green(ids: &[u32], key: u64, gamma: f64, context: usize) -> (usize, usize) {
+    let mut seen = HashSet::new();
+    let (mut tested, mut green) = (0, 0);
+    for window in ids.windows(context + 1) {
+        if !seen.insert(window) {
+            continue;
+        }
+        tested += 1;
+        if is_green(key, &window[..context], window[context], gamma) {
+            green += 1;
+        }
+    }
+    (tested, green)
+}
+
+/// z-score of `green` out of `tested` tokens against the chance share `gamma`.
+pub fn z_score(tested: usize, green: usize, gamma: f64) -> f64 {
+    if tested == 0 {
+        return 0.0;
+    }
+    let t = tested as f64;
+    (green as f64 - gamma * t) / (t * gamma * (1.0 - gamma)).sqrt()
+}
+
+/// One-sided p-value of `z` under the standard normal.
+fn p_value(z: f64) -> f64 {
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Abramowitz & Stegun 7.1.26, error < 1.5e-7).
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erfc = poly * (-x * x).exp();
+    if x >= 0.0 { erfc } else { 2.0 - erfc }
+}
+
+/// The known watermark schemes as an ensemble detector.
+pub struct Watermark {
+    schemes: Arc<Vec<Scheme>>,
+}
+
+impl Watermark {
+    /// Load the schemes file at `path`; tokenizer paths are relative to it.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let file: SchemesFile = toml::from_str(&data).map_err(|e| format!("Invalid watermark schemes: {e}"))?;
+        if file.schemes.is_empty() {
+            return Err("Watermark schemes file lists no `[[schemes]]`".to_string());
+        }
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let schemes = file
+            .schemes
+            .into_iter()
+            .map(|s| {
+                if !(s.gamma > 0.0 && s.gamma < 1.0) {
+                    return Err(format!("Scheme `{}`: gamma must be between 0 and 1", s.name));
+                }
+                if s.context == 0 {
+                    return Err(format!("Scheme `{}`: context must be at least 1", s.name));
+                }
+                let tokenizer_path = dir.join(&s.tokenizer);
+                let tokenizer = Tokenizer::from_file(&tokenizer_path)
+                    .map_err(|e| format!("Scheme `{}`: failed to load {}: {e}", s.name, tokenizer_path.display()))?;
+                Ok(Scheme {
+                    name: s.name,
+                    key: s.key,
+                    gamma: s.gamma,
+                    context: s.context,
+                    tokenizer,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self {
+            schemes: Arc::new(schemes),
+        })
+    }
+
+    pub fn scheme_names(&self) -> Vec<&str> {
+        self.schemes.iter().map(|s| s.name.as_str()).collect()
+    }
+}
+
+/// The scheme with the highest z-score on `text`.
+fn test(schemes: &[Scheme], text: &str, rules: &Rules) -> Result<WatermarkResult, String> {
+    let mut best: Option<WatermarkResult> = None;
+    for scheme in schemes {
+        let encoding = scheme
+            .tokenizer
+            .encode(text, false)
+            .map_err(|e| format!("Scheme `{}`: tokenization failed: {e}", scheme.name))?;
+        let (tested, green) = count_green(encoding.get_ids(), scheme.key, scheme.gamma, scheme.context);
+        let z = z_score(tested, green, scheme.gamma);
+        if best.as_ref().is_none_or(|b| z > b.z_score) {
+            best = Some(WatermarkResult {
+                scheme: scheme.name.clone(),
+                detected: tested >= rules.watermark.min_tokens && z >= rules.watermark.z_threshold,
+                z_score: z,
+                tokens: tested,
+                green,
+            });
+        }
+    }
+    best.ok_or_else(|| "No watermark schemes loaded".to_string())
+}
+
+#[async_trait]
+impl Detector for Watermark {
+    fn name(&self) -> &'static str {
+        "watermark"
+    }
+
+    async fn detect(&self, input: &Input) -> Result<Verdict, AppError> {
+        // Undo humanizer tricks (invisible characters, homoglyphs) that would
+        // break the token sequence, but keep links and hashtags: the generator
+        // watermarked them too
+        let text = tampering::normalize(&input.content).text;
+        let rules = input.rules.clone();
+        let schemes = self.schemes.clone();
+        let result = tokio::task::spawn_blocking(move || test(&schemes, &text, &rules))
+            .await
+            .map_err(|e| AppError::Internal(format!("Watermark detection panicked: {e}")))?
+            .map_err(AppError::Internal)?;
+
+        let signals = if result.detected { vec![format!("watermark_{}", result.scheme)] } else { Vec::new() };
+        Ok(Verdict {
+            // No watermark is no evidence either way
+            score: if result.detected { 10 } else { input.rules.prior.score.round() as u8 },
+            confidence: 1.0 - p_value(result.z_score),
+            signals,
+            watermark: Some(result),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Greedy watermarked ids: after each token, the smallest green id from `start`.
+    fn watermarked(n: usize, key: u64, gamma: f64) -> Vec<u32> {
+        let mut ids = vec![7u32];
+        while ids.len() < n {
+            let prev = *ids.last().unwrap();
+            let next = (ids.len() as u32 * 31..).find(|&t| is_green(key, &[prev], t, gamma)).unwrap();
+            ids.push(next);
+        }
+        ids
+    }
+
+    #[test]
+    fn test_watermarked_ids_score_high() {
+        let ids = watermarked(200, 42, 0.25);
+        let (tested, green) = count_green(&ids, 42, 0.25, 1);
+        assert_eq!(tested, 199);
+        assert_eq!(green, 199);
+        assert!(z_score(tested, green, 0.25) > 10.0);
+
+        // Another key sees chance-level green tokens
+        let (tested, green) = count_green(&ids, 43, 0.25, 1);
+        assert!(z_score(tested, green, 0.25).abs() < 4.0);
+    }
+
+    #[test]
+    fn test_repeated_pairs_counted_once() {
+        let ids = [1, 2, 1, 2, 1, 2, 1, 2];
+        assert_eq!(count_green(&ids, 1, 0.5, 1).0, 2);
+        assert_eq!(z_score(0, 0, 0.25), 0.0);
+        assert!((p_value(0.0) - 0.5).abs() < 1e-6);
+        assert!(p_value(4.0) < 1e-4);
+    }
+}
@@ -0,0 +1,147 @@
+//! `aidetector-server train`: fit the classifier detector to LLM-scored posts.
+//!
+//! Posts come from the `analyses` table (rows with an `llm_score`) or from a
+//! JSONL file of `{"content": "...", "platform": "linkedin", "score": 8}`
+//! lines (`platform` optional). Each post runs through the heuristic engine
+//! with the active rules (`RULES_PATH`), and posts scored at or above the
+//! threshold count as AI. Every fifth post is held out to compare the model
+//! with the hand-tuned heuristic score.
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::db;
+use crate::models::Platform;
+use crate::services::classifier::{self, Algorithm, Model, Sample};
+use crate::services::heuristics;
+use crate::services::rules::Rules;
+
+const USAGE: &str = "Usage: aidetector-server train [--db <url> | --jsonl <file>] --out <model.json> \
+                     [--model logistic|naive_bayes] [--threshold <0-10>]";
+
+/// Every n-th post is held out for validation.
+const HOLDOUT_EVERY: usize = 5;
+
+struct Options {
+    db: Option<String>,
+    jsonl: Option<PathBuf>,
+    out: PathBuf,
+    algorithm: Algorithm,
+    threshold: u8,
+}
+
+#[derive(Deserialize)]
+struct Line {
+    content: String,
+    platform: Option<Platform>,
+    score: u8,
+}
+
+/// Run the subcommand with the arguments after `train`.
+pub async fn run(args: &[String]) -> Result<(), String> {
+    let options = parse(args)?;
+    let rules_path = std::env::var("RULES_PATH").ok().filter(|s| !s.is_empty()).map(PathBuf::from);
+    let rules = Rules::load(rules_path.as_deref())?;
+
+    let posts: Vec<(String, Option<Platform>, u8)> = match &options.jsonl {
+        Some(path) => {
+            let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+            data.lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| {
+                    let line: Line = serde_json::from_str(line).map_err(|e| format!("Line {}: {e}", i + 1))?;
+                    Ok((line.content, line.platform, line.score))
+                })
+                .collect::<Result<_, String>>()?
+        }
+        None => {
+            let url = options
+                .db
+                .clone()
+                .unwrap_or_else(|| std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:data.db".to_string()));
+            let pool = db::init_pool(&url).await;
+            db::get_llm_scored(&pool)
+                .await
+                .map_err(|e| format!("Failed to read analyses: {e}"))?
+                .into_iter()
+                .map(|(content, platform, score)| {
+                    let platform = Platform::ALL.into_iter().find(|p| p.to_string() == platform);
+                    (content, platform, score.clamp(0, 10) as u8)
+                })
+                .collect()
+        }
+    };
+
+    let mut train = Vec::new();
+    let mut holdout = Vec::new();
+    for (i, (content, platform, score)) in posts.iter().enumerate() {
+        let result = heuristics::analyze(content, &rules, platform.as_ref());
+        let sample = Sample {
+            features: classifier::features(&result),
+            ai: *score >= options.threshold,
+        };
+        if i % HOLDOUT_EVERY == HOLDOUT_EVERY - 1 {
+            holdout.push((sample, result.score));
+        } else {
+            train.push(sample);
+        }
+    }
+    let ai = train.iter().filter(|s| s.ai).count();
+    println!("Training on {} posts ({ai} AI, {} human), {} held out", train.len(), train.len() - ai, holdout.len());
+
+    let model = Model::train(&train, options.algorithm)?;
+    if !holdout.is_empty() {
+        let accuracy = |correct: usize| 100.0 * correct as f64 / holdout.len() as f64;
+        let model_correct = holdout.iter().filter(|(s, _)| (model.predict(&s.features) >= 0.5) == s.ai).count();
+        let rules_correct = holdout
+            .iter()
+            .filter(|(s, heuristic)| (*heuristic >= options.threshold) == s.ai)
+            .count();
+        println!("Held-out accuracy: model {:.1}%, heuristic rules {:.1}%", accuracy(model_correct), accuracy(rules_correct));
+    }
+
+    let json = serde_json::to_string_pretty(&model).map_err(|e| e.to_string())?;
+    fs::write(&options.out, json).map_err(|e| format!("Failed to write {}: {e}", options.out.display()))?;
+    println!("Model written to {}", options.out.display());
+    Ok(())
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut db = None;
+    let mut jsonl = None;
+    let mut out = None;
+    let mut algorithm = Algorithm::Logistic;
+    let mut threshold = 6;
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| format!("{flag} needs a value\n{USAGE}"));
+        match flag.as_str() {
+            "--db" => db = Some(value()?),
+            "--jsonl" => jsonl = Some(PathBuf::from(value()?)),
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--model" => algorithm = value()?.parse()?,
+            "--threshold" => {
+                threshold = value()?
+                    .parse()
+                    .ok()
+                    .filter(|t| *t <= 10)
+                    .ok_or_else(|| "--threshold must be 0-10".to_string())?
+            }
+            other => return Err(format!("Unknown argument `{other}`\n{USAGE}")),
+        }
+    }
+    if db.is_some() && jsonl.is_some() {
+        return Err(format!("Use either --db or --jsonl\n{USAGE}"));
+    }
+    Ok(Options {
+        db,
+        jsonl,
+        out: out.ok_or_else(|| format!("--out is required\n{USAGE}"))?,
+        algorithm,
+        threshold,
+    })
+}